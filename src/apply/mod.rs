@@ -0,0 +1,247 @@
+use clap::Parser;
+use colored::Colorize;
+use dmtri::demeter::ops::v1alpha::Resource;
+use miette::{bail, Context as _, IntoDiagnostic};
+
+use crate::{
+    context::extract_context_data,
+    errors::Error,
+    ports::{manifest, patch, schema},
+    rpc,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// path to a json or yaml file with the port manifests, use - for stdin
+    #[arg(short, long)]
+    file: String,
+
+    /// delete ports of the project that aren't present in the manifests
+    #[arg(long, action)]
+    prune: bool,
+
+    /// only print the plan, without changing anything
+    #[arg(long, action)]
+    dry_run: bool,
+
+    /// apply the plan without asking for confirmation, including the deletes
+    /// of --prune
+    #[arg(long, short, action)]
+    yes: bool,
+}
+
+enum Action {
+    Create(manifest::Manifest),
+    Update {
+        current: Resource,
+        spec_patch: serde_json::Value,
+    },
+    Unchanged(Resource),
+    Delete(Resource),
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Create(x) => write!(
+                f,
+                "{} {} {}",
+                "+".green(),
+                x.kind,
+                x.name.as_deref().unwrap_or("<new>")
+            ),
+            Action::Update {
+                current,
+                spec_patch,
+            } => {
+                let fields = spec_patch
+                    .as_object()
+                    .map(|x| x.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+
+                write!(
+                    f,
+                    "{} {} {} ({}): {}",
+                    "~".yellow(),
+                    current.kind,
+                    current.name,
+                    current.id,
+                    fields
+                )
+            }
+            Action::Unchanged(x) => write!(f, "  {} {} ({})", x.kind, x.name, x.id),
+            Action::Delete(x) => write!(f, "{} {} {} ({})", "-".red(), x.kind, x.name, x.id),
+        }
+    }
+}
+
+fn define_plan(
+    manifests: Vec<manifest::Manifest>,
+    live: &[Resource],
+    prune: bool,
+) -> miette::Result<Vec<Action>> {
+    let targets = manifest::match_live(&manifests, live)?;
+
+    let mut plan = vec![];
+
+    for (manifest, current) in manifests.into_iter().zip(targets.iter()) {
+        let current = match current {
+            Some(x) => x,
+            None => {
+                plan.push(Action::Create(manifest));
+                continue;
+            }
+        };
+
+        let current_spec: serde_json::Value = serde_json::from_str(&current.spec)
+            .into_diagnostic()
+            .context(format!("parsing spec of port {}", current.name))?;
+
+        let desired = manifest.desired_spec(&current_spec);
+
+        if desired == current_spec {
+            plan.push(Action::Unchanged((*current).clone()));
+        } else {
+            plan.push(Action::Update {
+                current: (*current).clone(),
+                spec_patch: patch::diff(&current_spec, &desired),
+            });
+        }
+    }
+
+    if prune {
+        for resource in live {
+            if !targets.iter().flatten().any(|x| x.id == resource.id) {
+                plan.push(Action::Delete(resource.clone()));
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+fn validate_plan(
+    plan: &[Action],
+    metadata: &[rpc::metadata::ResourceMetadata],
+) -> miette::Result<()> {
+    for action in plan {
+        let (kind, spec) = match action {
+            Action::Create(x) => (x.kind.as_str(), x.spec.clone()),
+            Action::Update {
+                current,
+                spec_patch,
+            } => {
+                let mut spec: serde_json::Value =
+                    serde_json::from_str(&current.spec).into_diagnostic()?;
+                patch::apply(&mut spec, spec_patch);
+                (current.kind.as_str(), spec)
+            }
+            _ => continue,
+        };
+
+        let crd = match metadata.iter().find(|m| m.crd.spec.names.kind == kind) {
+            Some(x) => &x.crd,
+            None => bail!("unknown kind {kind}"),
+        };
+
+        schema::validate(crd, &spec)?;
+    }
+
+    Ok(())
+}
+
+fn print_plan(plan: &[Action]) {
+    let count = |f: fn(&Action) -> bool| plan.iter().filter(|x| f(x)).count();
+
+    println!(
+        "Plan: {} to create, {} to update, {} to delete, {} unchanged",
+        count(|x| matches!(x, Action::Create(_))),
+        count(|x| matches!(x, Action::Update { .. })),
+        count(|x| matches!(x, Action::Delete(_))),
+        count(|x| matches!(x, Action::Unchanged(_))),
+    );
+
+    for action in plan {
+        println!("  {action}");
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let _ctx = cli
+        .context
+        .as_ref()
+        .ok_or(miette::miette!("can't apply manifests without a context"))?;
+
+    let manifests = manifest::load_manifests(&args.file)?;
+
+    let (client, project_id, _) = extract_context_data(cli).await?;
+    let live = client.find_resources(&project_id).await?;
+    let metadata = client.find_metadata().await?;
+
+    let plan = define_plan(manifests, &live, args.prune)?;
+    validate_plan(&plan, &metadata)?;
+
+    print_plan(&plan);
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let changes = plan.iter().any(|x| !matches!(x, Action::Unchanged(_)));
+
+    if !changes {
+        return Ok(());
+    }
+
+    let deletes = plan.iter().any(|x| matches!(x, Action::Delete(_)));
+
+    if !args.yes {
+        let msg = match deletes {
+            true => "The plan deletes ports, this action cannot be undone. Do you want to proceed?",
+            false => "Do you want to apply the plan?",
+        };
+
+        let confirm = inquire::Confirm::new(msg).prompt().map_err(Error::from)?;
+
+        if !confirm {
+            return Err(Error::Aborted.into());
+        }
+    }
+
+    for action in plan {
+        match action {
+            Action::Create(manifest) => {
+                let spec = manifest.spec.to_string();
+                let result = client
+                    .create_resource(&project_id, &manifest.kind, &spec)
+                    .await?;
+
+                println!("Port {}({}) created", result.kind, result.id);
+
+                if manifest.id.is_none() {
+                    println!(
+                        "  add `id: {}` to the manifest to keep tracking it if its spec changes",
+                        result.id
+                    );
+                }
+            }
+            Action::Update {
+                current,
+                spec_patch,
+            } => {
+                let spec_patch = spec_patch.to_string();
+                client.update_resource(&current.id, &spec_patch).await?;
+
+                println!("Port {}({}) updated", current.kind, current.id);
+            }
+            Action::Delete(current) => {
+                client.delete_resource(&current.id).await?;
+
+                println!("Port {}({}) deleted", current.kind, current.id);
+            }
+            Action::Unchanged(_) => (),
+        }
+    }
+
+    Ok(())
+}
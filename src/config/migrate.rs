@@ -0,0 +1,62 @@
+use clap::Parser;
+
+use crate::{context::lock_config, errors::Error};
+
+use super::migrations;
+
+#[derive(Parser)]
+pub struct Args {
+    /// only list the pending migrations, exits with 10 if there are any
+    #[arg(long, action)]
+    check: bool,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let location = cli.dirs.root_dir().join("config.toml");
+
+    if !location.exists() {
+        println!("No config file found at {}", location.display());
+        return Ok(());
+    }
+
+    let _lock = lock_config(&cli.dirs)?;
+
+    let table = migrations::read_table(&location)?;
+    let version = migrations::version_of(&table)?;
+    let pending = migrations::pending(&table)?;
+
+    if pending.is_empty() {
+        println!("Config is up to date (version {version})");
+        return Ok(());
+    }
+
+    if args.check {
+        println!("Pending migrations:");
+
+        for migration in &pending {
+            println!(
+                "  v{} → v{}: {}",
+                migration.from,
+                migration.from + 1,
+                migration.description
+            );
+        }
+
+        return Err(Error::MigrationsPending(format!(
+            "config is at version {version}, {} migration(s) pending",
+            pending.len()
+        ))
+        .into());
+    }
+
+    for migration in migrations::migrate(&location, &cli.dirs)? {
+        println!(
+            "Migrated config to version {}: {} (previous layout at {}, without api keys)",
+            migration.from + 1,
+            migration.description,
+            migrations::backup_path(&location, migration.from).display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use miette::{Context as _, IntoDiagnostic};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use tracing::debug;
+
+use crate::{
+    context::{move_secrets_to_store, Config},
+    files,
+};
+
+/// Version of the config layout written by this build. Configs without a
+/// `version` key predate versioning and are treated as version 1.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Upgrades the config from `from` to `from + 1`.
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    apply: fn(&mut Table, &crate::dirs::Dirs) -> miette::Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "move plaintext api keys into the secret store",
+    apply: move_api_keys,
+}];
+
+fn move_api_keys(table: &mut Table, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let mut config: Config = Value::Table(table.clone())
+        .try_into()
+        .into_diagnostic()
+        .context("deserializing config")?;
+
+    move_secrets_to_store(&mut config, dirs)?;
+
+    *table = Table::try_from(config)
+        .into_diagnostic()
+        .context("serializing config")?;
+
+    Ok(())
+}
+
+pub fn read_table(location: &Path) -> miette::Result<Table> {
+    let toml = std::fs::read_to_string(location)
+        .into_diagnostic()
+        .context("reading project config file")?;
+
+    toml.parse()
+        .into_diagnostic()
+        .context("parsing config file")
+}
+
+pub fn version_of(table: &Table) -> miette::Result<u32> {
+    match table.get("version") {
+        None => Ok(1),
+        Some(Value::Integer(x)) => u32::try_from(*x)
+            .into_diagnostic()
+            .context("invalid config version"),
+        Some(x) => miette::bail!("invalid config version {x}"),
+    }
+}
+
+/// Migrations that need to run, in order, to bring the config up to date.
+pub fn pending(table: &Table) -> miette::Result<Vec<&'static Migration>> {
+    let version = version_of(table)?;
+
+    if version > CURRENT_VERSION {
+        miette::bail!(
+            "config version {version} was written by a newer dmtrctl, this one supports up to version {CURRENT_VERSION}"
+        );
+    }
+
+    Ok(MIGRATIONS.iter().filter(|m| m.from >= version).collect())
+}
+
+pub fn backup_path(location: &Path, version: u32) -> PathBuf {
+    location.with_extension(format!("toml.v{version}.bak"))
+}
+
+/// A copy of the config without plaintext api keys, which must never be left
+/// behind in a backup. The keys are in the secret store by the time the
+/// migration that needs them is done, but the backup doesn't point to them,
+/// so it's a snapshot of the layout rather than a config to restore.
+fn without_api_keys(table: &Table) -> Table {
+    let mut table = table.clone();
+
+    let contexts = table
+        .get_mut("contexts")
+        .and_then(|x| x.as_table_mut())
+        .into_iter()
+        .flat_map(|x| x.iter_mut().map(|(_, ctx)| ctx));
+
+    for ctx in contexts {
+        if let Some(auth) = ctx.get_mut("auth").and_then(|x| x.as_table_mut()) {
+            auth.remove("token");
+        }
+    }
+
+    table
+}
+
+/// Heads every backup, so that whoever finds it knows what restoring it does.
+fn backup_header(version: u32) -> String {
+    format!(
+        "# config.toml as of version {version}, before migrating it. The api keys were\n\
+         # left out, so contexts restored from this file are logged out and need\n\
+         # `dmtrctl init` again.\n\n"
+    )
+}
+
+/// Runs the pending migrations one at a time, saving a private snapshot of
+/// the file (minus any api keys) before each rewrite. Callers are expected to
+/// hold the config lock.
+pub fn migrate(
+    location: &Path,
+    dirs: &crate::dirs::Dirs,
+) -> miette::Result<Vec<&'static Migration>> {
+    let mut table = read_table(location)?;
+    let pending = pending(&table)?;
+
+    for migration in pending.iter() {
+        debug!(from = migration.from, "{}", migration.description);
+
+        let current = toml::to_string(&without_api_keys(&table)).into_diagnostic()?;
+        let backup = backup_header(migration.from) + &current;
+        files::write_private(&backup_path(location, migration.from), backup.as_bytes())
+            .context("backing up config")?;
+
+        (migration.apply)(&mut table, dirs)
+            .context(format!("migrating config from version {}", migration.from))?;
+
+        table.insert("version".into(), Value::Integer(migration.from as i64 + 1));
+
+        let migrated = toml::to_string(&table).into_diagnostic()?;
+        files::write_private(location, migrated.as_bytes()).context("writing config file")?;
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_CONFIG: &str = r#"
+default_context = "alpha"
+secret_store = "file"
+
+[contexts.alpha.namespace]
+name = "alpha-ns"
+
+[contexts.alpha.auth]
+name = "default"
+method = "ApiKey"
+token = "dmtr_apikey_abcdefghijklmnop"
+"#;
+
+    fn test_dirs(name: &str) -> crate::dirs::Dirs {
+        let root = std::env::temp_dir().join(format!("dmtrctl-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        crate::dirs::Dirs::try_new(Some(&root)).unwrap()
+    }
+
+    #[test]
+    fn unversioned_config_is_version_1() {
+        let table: Table = V1_CONFIG.parse().unwrap();
+
+        assert_eq!(version_of(&table).unwrap(), 1);
+        assert_eq!(pending(&table).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn current_config_has_nothing_pending() {
+        let table: Table = format!("version = {CURRENT_VERSION}").parse().unwrap();
+
+        assert!(pending(&table).unwrap().is_empty());
+    }
+
+    #[test]
+    fn newer_config_is_rejected() {
+        let table: Table = format!("version = {}", CURRENT_VERSION + 1)
+            .parse()
+            .unwrap();
+
+        assert!(pending(&table).is_err());
+    }
+
+    #[test]
+    fn invalid_version_is_rejected() {
+        let table: Table = "version = \"two\"".parse().unwrap();
+
+        assert!(version_of(&table).is_err());
+    }
+
+    #[test]
+    fn backup_drops_api_keys() {
+        let table: Table = V1_CONFIG.parse().unwrap();
+        let backup = toml::to_string(&without_api_keys(&table)).unwrap();
+
+        assert!(!backup.contains("token"));
+        assert!(backup.contains("alpha-ns"));
+    }
+
+    #[test]
+    fn migrates_v1_to_v2() {
+        crate::secrets::with_passphrase("test-passphrase", migrates_v1_to_v2_unlocked);
+    }
+
+    fn migrates_v1_to_v2_unlocked() {
+        let dirs = test_dirs("migrate");
+        let location = dirs.root_dir().join("config.toml");
+        std::fs::write(&location, V1_CONFIG).unwrap();
+
+        let applied = migrate(&location, &dirs).unwrap();
+        assert_eq!(applied.len(), 1);
+
+        let migrated = std::fs::read_to_string(&location).unwrap();
+        assert!(!migrated.contains("dmtr_apikey_"));
+
+        let table: Table = migrated.parse().unwrap();
+        assert_eq!(version_of(&table).unwrap(), CURRENT_VERSION);
+        assert!(pending(&table).unwrap().is_empty());
+
+        let backup = std::fs::read_to_string(backup_path(&location, 1)).unwrap();
+        assert!(backup.starts_with(&backup_header(1)));
+        assert!(!backup.contains("token"));
+        assert!(!backup.contains("dmtr_apikey_"));
+
+        // the snapshot is still valid toml, only the keys are missing
+        let snapshot: Table = backup.parse().unwrap();
+        assert_eq!(version_of(&snapshot).unwrap(), 1);
+
+        let config: Config = toml::from_str(&migrated).unwrap();
+        let token = config.contexts["alpha"].auth.resolve_token(&dirs).unwrap();
+        assert_eq!(token, "dmtr_apikey_abcdefghijklmnop");
+
+        // a second run has nothing left to do
+        assert!(migrate(&location, &dirs).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(dirs.root_dir());
+    }
+}
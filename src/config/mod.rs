@@ -0,0 +1,22 @@
+use clap::Parser;
+
+mod migrate;
+pub mod migrations;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Parser)]
+pub enum Commands {
+    /// Upgrade the config file to the layout of this version
+    Migrate(migrate::Args),
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    match args.command {
+        Commands::Migrate(x) => migrate::run(x, cli).await,
+    }
+}
@@ -1,28 +1,97 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Once,
+};
 
 use miette::{Context as MietteContext, IntoDiagnostic};
 use serde::{Deserialize, Serialize};
+use tracing::debug;
 
-use crate::rpc;
+use crate::{config::migrations, errors::Error, files, rpc, secrets};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
+    /// Layout version, see `config::migrations`
+    #[serde(default)]
+    pub version: u32,
+
     pub contexts: HashMap<String, Context>,
     pub default_context: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<RpcConfig>,
+
+    /// Where new api keys are stored, overridden by `DMTR_SECRET_STORE`.
+    /// Defaults to the OS keyring when there's one available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_store: Option<secrets::Backend>,
+}
+
+impl Config {
+    fn preferred_secret_store(&self) -> miette::Result<Option<secrets::Backend>> {
+        match std::env::var("DMTR_SECRET_STORE") {
+            Ok(x) => clap::ValueEnum::from_str(&x, true)
+                .map(Some)
+                .map_err(|err| miette::miette!("invalid DMTR_SECRET_STORE: {err}")),
+            Err(_) => Ok(self.secret_store),
+        }
+    }
+}
+
+/// Connection settings for the Demeter RPC API, overridden by the
+/// `--timeout` and `--retries` flags.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RpcConfig {
+    /// Timeout in seconds for connecting and for each request
+    pub timeout: Option<u64>,
+
+    /// How many times idempotent calls are retried on transient errors
+    pub retries: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Context {
     pub namespace: Namespace,
     pub auth: Auth,
+
+    /// Endpoint of the Demeter RPC API, for contexts that live in another
+    /// cluster than the public one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+
+    /// Base url of the auth server used by `dmtrctl init` to log in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_url: Option<String>,
+
+    /// Registry (and path prefix) where `pages deploy` pushes images
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages_registry: Option<String>,
+
+    /// Domain where node ports are exposed, `ports tunnel` connects to
+    /// `<auth token>.<domain>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tunnel_domain: Option<String>,
 }
 
 impl Context {
+    /// A context against the public Demeter endpoints.
+    pub fn new(namespace: Namespace, auth: Auth) -> Self {
+        Self {
+            namespace,
+            auth,
+            rpc_url: None,
+            auth_url: None,
+            pages_registry: None,
+            tunnel_domain: None,
+        }
+    }
+
     pub async fn ephemeral(namespace: &str, api_key: &str) -> miette::Result<Self> {
         let namespace = crate::context::Namespace::new(namespace, None);
         let auth = crate::context::Auth::api_key(api_key);
 
-        Ok(Self { namespace, auth })
+        Ok(Self::new(namespace, auth))
     }
 }
 
@@ -44,7 +113,19 @@ impl Namespace {
 pub struct Auth {
     pub name: String,
     pub method: String,
+
+    /// Only set in memory, or in configs written before api keys were moved
+    /// to a secret store (these get migrated on load)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub token: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<secrets::SecretRef>,
+
+    /// Id of the project secret behind the api key, known when `init`
+    /// created the key, so that `logout --revoke` can revoke it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
 }
 
 impl Auth {
@@ -53,13 +134,52 @@ impl Auth {
             name: "default".to_owned(),
             method: "ApiKey".to_owned(),
             token: api_key.to_owned(),
+            secret: None,
+            key_id: None,
+        }
+    }
+
+    /// False once the context has been logged out.
+    pub fn has_credentials(&self) -> bool {
+        !self.token.is_empty() || self.secret.is_some()
+    }
+
+    /// Returns the token, reading it from the secret store if it isn't
+    /// loaded yet.
+    pub fn resolve_token(&self, dirs: &crate::dirs::Dirs) -> miette::Result<String> {
+        match (&self.secret, self.token.is_empty()) {
+            (Some(secret), true) => secrets::load(secret, dirs),
+            (None, true) => Err(Error::Unauthenticated(
+                "this context was logged out, it has no api key".into(),
+            )
+            .into()),
+            _ => Ok(self.token.clone()),
         }
     }
 }
 
-pub fn load_config(dirs: &crate::dirs::Dirs) -> miette::Result<Config> {
-    let location = dirs.root_dir().join("config.toml");
+/// Hides all but a few characters on each end of a secret, enough to tell
+/// keys apart without leaking them.
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+
+    if chars.len() <= 12 {
+        return "*".repeat(8);
+    }
 
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+
+    format!("{head}********{tail}")
+}
+
+/// Takes the advisory lock that serializes config changes across dmtrctl
+/// processes, hold it from loading the config until it's saved.
+pub fn lock_config(dirs: &crate::dirs::Dirs) -> miette::Result<files::LockGuard> {
+    files::lock_exclusive(&dirs.root_dir().join("config.lock"))
+}
+
+fn read_config(location: &Path) -> miette::Result<Config> {
     if !location.exists() {
         return Ok(Config::default());
     }
@@ -75,22 +195,88 @@ pub fn load_config(dirs: &crate::dirs::Dirs) -> miette::Result<Config> {
     Ok(dto)
 }
 
-pub fn save_config(value: Config, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+pub fn load_config(dirs: &crate::dirs::Dirs) -> miette::Result<Config> {
+    static PERMISSIONS_CHECKED: Once = Once::new();
+
     let location = dirs.root_dir().join("config.toml");
 
+    PERMISSIONS_CHECKED.call_once(|| files::warn_if_exposed(&location));
+
+    if !location.exists() {
+        return Ok(Config::default());
+    }
+
+    let table = migrations::read_table(&location)?;
+
+    if !migrations::pending(&table)?.is_empty() {
+        static MIGRATION_FAILED: Once = Once::new();
+
+        // the migration reads the file again once it owns the lock, in case
+        // another process got there first
+        let _lock = lock_config(dirs)?;
+
+        // an outdated config still works as is, so when the migration can't
+        // run (eg: the secret store can't be unlocked without a terminal) the
+        // command goes on and the migration is retried next time
+        if let Err(err) = migrations::migrate(&location, dirs) {
+            let reason: Vec<_> = err.chain().map(|x| x.to_string()).collect();
+
+            MIGRATION_FAILED.call_once(|| {
+                eprintln!(
+                    "warning: couldn't upgrade {}, it will be retried: {}",
+                    location.display(),
+                    reason.join(": ")
+                )
+            });
+        }
+    }
+
+    read_config(&location)
+}
+
+/// Stores any api key that's only held in memory and replaces it with a
+/// reference to the secret store.
+pub fn move_secrets_to_store(config: &mut Config, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let backend = config.preferred_secret_store()?;
+
+    for ctx in config.contexts.values_mut() {
+        if ctx.auth.token.is_empty() {
+            continue;
+        }
+
+        let secret = secrets::store(&ctx.auth.token, backend, dirs)?;
+
+        if let Some(previous) = ctx.auth.secret.replace(secret) {
+            secrets::delete(&previous, dirs)?;
+        }
+
+        ctx.auth.token.clear();
+    }
+
+    Ok(())
+}
+
+/// Writes the config, so that the file only references api keys, these are
+/// moved into the secret store first.
+pub fn save_config(mut value: Config, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let location = dirs.root_dir().join("config.toml");
+    let _lock = lock_config(dirs)?;
+
+    value.version = migrations::CURRENT_VERSION;
+    move_secrets_to_store(&mut value, dirs)?;
+
     let toml = toml::to_string(&value)
         .into_diagnostic()
         .context("serializing config")?;
 
-    std::fs::write(location, toml)
-        .into_diagnostic()
-        .context("writing config file")?;
+    files::write_private(&location, toml.as_bytes()).context("writing config file")?;
 
     Ok(())
 }
 
 pub fn clear_config(dirs: &crate::dirs::Dirs) -> miette::Result<()> {
     let location = dirs.root_dir().join("config.toml");
+    let _lock = lock_config(dirs)?;
 
     std::fs::remove_file(location)
         .into_diagnostic()
@@ -100,6 +286,7 @@ pub fn clear_config(dirs: &crate::dirs::Dirs) -> miette::Result<()> {
 }
 
 pub fn set_default_context(name: &str, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
     let mut config = load_config(dirs)?;
 
     config.default_context = Some(name.to_string());
@@ -115,9 +302,11 @@ pub fn overwrite_context(
     set_default: bool,
     dirs: &crate::dirs::Dirs,
 ) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
     let mut config = load_config(dirs)?;
 
-    config.contexts.insert(name.to_string(), dto);
+    let secret = dto.auth.secret.clone();
+    let previous = config.contexts.insert(name.to_string(), dto);
 
     if set_default {
         config.default_context = Some(name.to_string());
@@ -125,6 +314,120 @@ pub fn overwrite_context(
 
     save_config(config, dirs)?;
 
+    // the api key of the replaced context isn't referenced anymore
+    if let Some(orphan) = previous.and_then(|x| x.auth.secret) {
+        if Some(&orphan) != secret.as_ref() {
+            secrets::delete(&orphan, dirs)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves a new context, failing if there's one with the same name already.
+pub fn add_context(name: &str, dto: Context, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
+    let mut config = load_config(dirs)?;
+
+    if config.contexts.contains_key(name) {
+        miette::bail!("context {name} already exists");
+    }
+
+    config.contexts.insert(name.to_string(), dto);
+
+    save_config(config, dirs)
+}
+
+/// Changes a saved context in place, keeping everything `f` doesn't touch.
+pub fn update_context(
+    name: &str,
+    dirs: &crate::dirs::Dirs,
+    f: impl FnOnce(&mut Context),
+) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
+    let mut config = load_config(dirs)?;
+
+    let ctx = config
+        .contexts
+        .get_mut(name)
+        .ok_or(Error::NotFound(format!("context {name} not found")))?;
+
+    f(ctx);
+
+    save_config(config, dirs)
+}
+
+pub fn rename_context(old: &str, new: &str, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
+    let mut config = load_config(dirs)?;
+
+    if config.contexts.contains_key(new) {
+        miette::bail!("context {new} already exists");
+    }
+
+    let dto = config
+        .contexts
+        .remove(old)
+        .ok_or(Error::NotFound(format!("context {old} not found")))?;
+
+    config.contexts.insert(new.to_string(), dto);
+
+    if config.default_context.as_deref() == Some(old) {
+        config.default_context = Some(new.to_string());
+    }
+
+    save_config(config, dirs)?;
+
+    Ok(())
+}
+
+/// Removes a context from the config, returns true if it was the default one,
+/// which leaves the config without a default.
+pub fn delete_context(name: &str, dirs: &crate::dirs::Dirs) -> miette::Result<bool> {
+    let _lock = lock_config(dirs)?;
+    let mut config = load_config(dirs)?;
+
+    let removed = config
+        .contexts
+        .remove(name)
+        .ok_or(Error::NotFound(format!("context {name} not found")))?;
+
+    let was_default = config.default_context.as_deref() == Some(name);
+
+    if was_default {
+        config.default_context = None;
+    }
+
+    save_config(config, dirs)?;
+
+    if let Some(secret) = removed.auth.secret {
+        secrets::delete(&secret, dirs)?;
+    }
+
+    Ok(was_default)
+}
+
+/// Removes the api key of a context, keeping the rest of it so that `init`
+/// can log in to it again.
+pub fn logout_context(name: &str, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    let _lock = lock_config(dirs)?;
+    let mut config = load_config(dirs)?;
+
+    let ctx = config
+        .contexts
+        .get_mut(name)
+        .ok_or(Error::NotFound(format!("context {name} not found")))?;
+
+    let secret = ctx.auth.secret.take();
+    ctx.auth.token.clear();
+    ctx.auth.key_id = None;
+
+    save_config(config, dirs)?;
+
+    if let Some(secret) = secret {
+        secrets::delete(&secret, dirs)?;
+    }
+
     Ok(())
 }
 
@@ -149,30 +452,222 @@ pub fn load_default_context(dirs: &crate::dirs::Dirs) -> miette::Result<Option<C
     Ok(None)
 }
 
+pub const PROJECT_FILE_NAME: &str = ".dmtr.toml";
+
+/// Pins the context used inside a directory tree. The keys can be at the top
+/// level of the file or inside a `[demeter]` section.
+#[derive(Debug, Deserialize, Default)]
+pub struct ProjectFile {
+    pub context: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProjectFileDto {
+    demeter: Option<ProjectFile>,
+
+    #[serde(flatten)]
+    top_level: ProjectFile,
+}
+
+/// Walks up from the given directory looking for a project file.
+pub fn find_project_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+pub fn load_project_file(path: &Path) -> miette::Result<ProjectFile> {
+    let toml = std::fs::read_to_string(path)
+        .into_diagnostic()
+        .context(format!("reading {}", path.display()))?;
+
+    let dto: ProjectFileDto = toml::from_str(&toml)
+        .into_diagnostic()
+        .context(format!("parsing {}", path.display()))?;
+
+    Ok(dto.demeter.unwrap_or(dto.top_level))
+}
+
+/// Where the active context was taken from, listed in order of precedence.
+#[derive(Debug, Clone)]
+pub enum ContextSource {
+    /// `--namespace` and `--api-key` (or their env vars)
+    Credentials,
+    /// `--context`
+    Flag,
+    /// a `.dmtr.toml` found in the current directory or above
+    ProjectFile(PathBuf),
+    /// `DMTR_CONTEXT`
+    Env,
+    /// `default_context` in the config
+    Default,
+}
+
+impl std::fmt::Display for ContextSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextSource::Credentials => f.write_str("--namespace and --api-key"),
+            ContextSource::Flag => f.write_str("--context"),
+            ContextSource::Env => f.write_str("DMTR_CONTEXT"),
+            ContextSource::ProjectFile(path) => write!(f, "{}", path.display()),
+            ContextSource::Default => f.write_str("default context"),
+        }
+    }
+}
+
+fn load_pinned_context(
+    path: &Path,
+    dirs: &crate::dirs::Dirs,
+) -> miette::Result<Option<(String, Context)>> {
+    let pinned = load_project_file(path)?;
+
+    // a pinned project must never fall back to some other context, that's
+    // how commands end up running against the wrong one
+    match (pinned.context, pinned.namespace) {
+        (Some(name), _) => load_context_by_name(&name, dirs)?
+            .map(|ctx| Some((name.clone(), ctx)))
+            .ok_or(
+                Error::NotFound(format!(
+                    "context {name} pinned by {} not found",
+                    path.display()
+                ))
+                .into(),
+            ),
+        (None, Some(namespace)) => load_config(dirs)?
+            .contexts
+            .into_iter()
+            .find(|(_, ctx)| ctx.namespace.name == namespace)
+            .map(Some)
+            .ok_or(
+                Error::NotFound(format!(
+                    "no saved context for namespace {namespace} pinned by {}, run `dmtrctl init`",
+                    path.display()
+                ))
+                .into(),
+            ),
+        (None, None) => Ok(None),
+    }
+}
+
+/// The name of the active context and where it was taken from.
+#[derive(Debug, Clone)]
+pub struct ContextOrigin {
+    pub name: String,
+    pub source: ContextSource,
+}
+
+/// Picks the context to use: explicit credentials, then `--context`, then
+/// the closest `.dmtr.toml`, then `DMTR_CONTEXT`, then the default context.
 pub async fn infer_context(
     name: Option<&str>,
+    name_from_env: bool,
     namespace: Option<&str>,
     api_key: Option<&str>,
     dirs: &crate::dirs::Dirs,
-) -> miette::Result<Option<Context>> {
-    match (name, namespace, api_key) {
-        (None, Some(namespace), Some(ak)) => Ok(Some(Context::ephemeral(namespace, ak).await?)),
-        (None, None, Some(_)) => Err(miette::miette!("missing project id value")),
-        (None, Some(_), None) => Err(miette::miette!("missing api key value")),
-        (Some(context), _, _) => load_context_by_name(context, dirs),
-        _ => load_default_context(dirs),
-    }
-}
+) -> miette::Result<Option<(Context, ContextOrigin)>> {
+    let (flag, env) = match name_from_env {
+        true => (None, name),
+        false => (name, None),
+    };
+
+    let (source, label, ctx) = match (flag, namespace, api_key) {
+        (None, Some(namespace), Some(ak)) => (
+            ContextSource::Credentials,
+            namespace.to_owned(),
+            Some(Context::ephemeral(namespace, ak).await?),
+        ),
+        (None, None, Some(_)) if env.is_none() => {
+            return Err(miette::miette!("missing project id value"))
+        }
+        (None, Some(_), None) if env.is_none() => {
+            return Err(miette::miette!("missing api key value"))
+        }
+        (Some(context), _, _) => (
+            ContextSource::Flag,
+            context.to_owned(),
+            load_context_by_name(context, dirs)?,
+        ),
+        _ => {
+            let pinned = match std::env::current_dir()
+                .ok()
+                .as_deref()
+                .and_then(find_project_file)
+            {
+                Some(path) => load_pinned_context(&path, dirs)?.map(|x| (path, x)),
+                None => None,
+            };
+
+            match (pinned, env) {
+                (Some((path, (name, ctx))), env) => {
+                    // the project file is more specific than the shell, but
+                    // say so in case the env var was set on purpose
+                    if let Some(env) = env.filter(|x| *x != name) {
+                        eprintln!(
+                            "warning: using context {name} pinned by {} instead of DMTR_CONTEXT={env}",
+                            path.display()
+                        );
+                    }
+
+                    (ContextSource::ProjectFile(path), name, Some(ctx))
+                }
+                (None, Some(env)) => (
+                    ContextSource::Env,
+                    env.to_owned(),
+                    load_context_by_name(env, dirs)?,
+                ),
+                (None, None) => {
+                    let name = load_config(dirs)?.default_context.unwrap_or_default();
+                    (ContextSource::Default, name, load_default_context(dirs)?)
+                }
+            }
+        }
+    };
+
+    let ctx = match ctx {
+        Some(x) => x,
+        None => {
+            debug!(%source, "no context found for {label}");
+            return Ok(None);
+        }
+    };
 
-pub async fn extract_context_data(cli: &crate::Cli) -> miette::Result<(String, String, String)> {
-    let api_key = cli.context.as_ref().unwrap().auth.token.clone();
-    let namespace = cli.context.as_ref().unwrap().namespace.name.clone();
+    debug!(%source, "using context {label}");
 
-    let project = rpc::projects::find_by_namespace(
-        rpc::auth::Credential::Secret((namespace.clone(), api_key.clone())),
-        &namespace,
-    )
-    .await?;
+    let origin = ContextOrigin {
+        name: label,
+        source,
+    };
+
+    Ok(Some((ctx, origin)))
+}
 
-    Ok((api_key, project.id, namespace))
+/// Resolves the project of the current context and returns a client
+/// authenticated against it, along with the project id and namespace.
+pub async fn extract_context_data(
+    cli: &crate::Cli,
+) -> miette::Result<(rpc::DemeterClient, String, String)> {
+    let ctx = cli
+        .context
+        .as_ref()
+        .ok_or(miette::miette!("missing context"))?;
+
+    let api_key = ctx.auth.resolve_token(&cli.dirs)?;
+    let namespace = ctx.namespace.name.clone();
+
+    let project = cli
+        .client
+        .with_credential(rpc::auth::Credential::Secret((
+            namespace.clone(),
+            api_key.clone(),
+        )))
+        .find_project_by_namespace(&namespace)
+        .await?;
+
+    let client = cli
+        .client
+        .with_credential(rpc::auth::Credential::Secret((project.id.clone(), api_key)));
+
+    Ok((client, project.id, namespace))
 }
@@ -0,0 +1,20 @@
+use clap::Parser;
+
+use crate::context::ContextSource;
+
+#[derive(Parser)]
+pub struct Args {}
+
+pub async fn run(_args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let origin = match &cli.context_origin {
+        Some(x) => x,
+        None => miette::bail!("no context in use, run `dmtrctl context use <name>`"),
+    };
+
+    match &origin.source {
+        ContextSource::Default => println!("{}", origin.name),
+        source => println!("{} (from {source})", origin.name),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use clap::Parser;
+
+use crate::{context::delete_context, errors::Error};
+
+#[derive(Parser)]
+pub struct Args {
+    /// name of the context to delete
+    name: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let msg = format!(
+        "You are about to delete the context {}, along with its api key. Do you want to proceed?",
+        args.name
+    );
+
+    let confirm = inquire::Confirm::new(&msg).prompt().map_err(Error::from)?;
+
+    if !confirm {
+        return Err(Error::Aborted.into());
+    }
+
+    let was_default = delete_context(&args.name, &cli.dirs)?;
+
+    println!("Context {} deleted", args.name);
+
+    if was_default {
+        println!("It was the default context, run `dmtrctl context use <name>` to pick another");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use clap::Parser;
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, ContentArrangement, Table};
+
+use crate::context::{load_config, mask_secret};
+
+#[derive(Parser)]
+pub struct Args {}
+
+pub async fn run(_args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let config = load_config(&cli.dirs)?;
+
+    if config.contexts.is_empty() {
+        println!("No contexts found, run `dmtrctl init` to create one");
+        return Ok(());
+    }
+
+    let mut names: Vec<&String> = config.contexts.keys().collect();
+    names.sort();
+
+    let mut table = Table::new();
+
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Default", "Name", "Namespace", "Project", "API Key"]);
+
+    for name in names {
+        let ctx = &config.contexts[name];
+
+        let default = match config.default_context.as_ref() == Some(name) {
+            true => "*",
+            false => "",
+        };
+
+        // keys in a secret store aren't read, that could mean a prompt for
+        // each of them
+        let api_key = match (&ctx.auth.secret, ctx.auth.token.is_empty()) {
+            (_, false) => mask_secret(&ctx.auth.token),
+            (Some(secret), true) => format!("<in {}>", secret.backend),
+            (None, true) => "<logged out>".to_owned(),
+        };
+
+        table.add_row(vec![
+            default,
+            name,
+            &ctx.namespace.name,
+            ctx.namespace.caption.as_deref().unwrap_or_default(),
+            &api_key,
+        ]);
+    }
+
+    println!("{table}");
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use clap::Parser;
+
+mod current;
+mod delete;
+mod list;
+mod rename;
+mod select;
+mod set;
+mod show;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Parser)]
+pub enum Commands {
+    /// List the saved contexts
+    #[command(alias = "ls")]
+    List(list::Args),
+    /// Print the name of the context in use
+    Current(current::Args),
+    /// Make a saved context the default one
+    Use(select::Args),
+    /// Rename a saved context
+    Rename(rename::Args),
+    /// Delete a saved context
+    #[command(alias = "rm")]
+    Delete(delete::Args),
+    /// Show the details of a saved context
+    Show(show::Args),
+    /// Change the endpoints of a saved context
+    Set(set::Args),
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    match args.command {
+        Commands::List(x) => list::run(x, cli).await,
+        Commands::Current(x) => current::run(x, cli).await,
+        Commands::Use(x) => select::run(x, cli).await,
+        Commands::Rename(x) => rename::run(x, cli).await,
+        Commands::Delete(x) => delete::run(x, cli).await,
+        Commands::Show(x) => show::run(x, cli).await,
+        Commands::Set(x) => set::run(x, cli).await,
+    }
+}
@@ -0,0 +1,19 @@
+use clap::Parser;
+
+use crate::context::rename_context;
+
+#[derive(Parser)]
+pub struct Args {
+    /// current name of the context
+    old: String,
+
+    /// new name for the context
+    new: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    rename_context(&args.old, &args.new, &cli.dirs)?;
+
+    println!("Context {} renamed to {}", args.old, args.new);
+    Ok(())
+}
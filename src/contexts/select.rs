@@ -0,0 +1,23 @@
+use clap::Parser;
+
+use crate::{
+    context::{load_context_by_name, set_default_context},
+    errors::Error,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// name of the context to use by default
+    name: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    if load_context_by_name(&args.name, &cli.dirs)?.is_none() {
+        return Err(Error::NotFound(format!("context {} not found", args.name)).into());
+    }
+
+    set_default_context(&args.name, &cli.dirs)?;
+
+    println!("Switched to context {}", args.name);
+    Ok(())
+}
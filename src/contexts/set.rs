@@ -0,0 +1,71 @@
+use clap::Parser;
+
+use crate::context::update_context;
+
+#[derive(Parser)]
+pub struct Args {
+    /// name of the context to change
+    name: String,
+
+    /// endpoint of the Demeter RPC API, an empty value resets it to the
+    /// public one
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// base url of the auth server used to log in, an empty value resets it
+    /// to the public one
+    #[arg(long)]
+    auth_url: Option<String>,
+
+    /// registry where `pages deploy` pushes images, an empty value resets it
+    /// to the public one
+    #[arg(long)]
+    pages_registry: Option<String>,
+
+    /// domain where node ports are exposed for `ports tunnel`, an empty value
+    /// resets it to the public one
+    #[arg(long)]
+    tunnel_domain: Option<String>,
+}
+
+/// An empty value clears the field, so the public default applies again.
+fn assign(field: &mut Option<String>, value: Option<String>) {
+    if let Some(value) = value {
+        *field = Some(value).filter(|x| !x.is_empty());
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let Args {
+        name,
+        rpc_url,
+        auth_url,
+        pages_registry,
+        tunnel_domain,
+    } = args;
+
+    if rpc_url.is_none()
+        && auth_url.is_none()
+        && pages_registry.is_none()
+        && tunnel_domain.is_none()
+    {
+        miette::bail!(
+            "nothing to change, pass at least one of --rpc-url, --auth-url, --pages-registry or --tunnel-domain"
+        );
+    }
+
+    if let Some(url) = rpc_url.as_deref().filter(|x| !x.is_empty()) {
+        // fail early instead of saving a context that can't connect
+        crate::rpc::DemeterClient::new(url, Default::default())?;
+    }
+
+    update_context(&name, &cli.dirs, |ctx| {
+        assign(&mut ctx.rpc_url, rpc_url);
+        assign(&mut ctx.auth_url, auth_url);
+        assign(&mut ctx.pages_registry, pages_registry);
+        assign(&mut ctx.tunnel_domain, tunnel_domain);
+    })?;
+
+    println!("Context {name} updated");
+    Ok(())
+}
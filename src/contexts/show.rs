@@ -0,0 +1,63 @@
+use clap::Parser;
+
+use crate::{
+    context::{load_config, mask_secret},
+    errors::Error,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// name of the context to show
+    name: String,
+
+    /// print the api key instead of a masked version
+    #[arg(long, action)]
+    reveal: bool,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let config = load_config(&cli.dirs)?;
+
+    let ctx = config
+        .contexts
+        .get(&args.name)
+        .ok_or(Error::NotFound(format!("context {} not found", args.name)))?;
+
+    let api_key = match (ctx.auth.has_credentials(), args.reveal) {
+        (false, _) => "<logged out>".to_owned(),
+        (true, true) => ctx.auth.resolve_token(&cli.dirs)?,
+        (true, false) => mask_secret(&ctx.auth.resolve_token(&cli.dirs)?),
+    };
+
+    let is_default = config.default_context.as_ref() == Some(&args.name);
+
+    println!("Name:       {}", args.name);
+    println!("Default:    {}", if is_default { "yes" } else { "no" });
+    println!("Namespace:  {}", ctx.namespace.name);
+    println!(
+        "Project:    {}",
+        ctx.namespace.caption.as_deref().unwrap_or("<none>")
+    );
+    println!("Auth:       {} ({})", ctx.auth.method, ctx.auth.name);
+    println!("API Key:    {api_key}");
+    println!(
+        "RPC URL:    {}",
+        ctx.rpc_url
+            .as_deref()
+            .unwrap_or(crate::rpc::DEFAULT_RPC_URL)
+    );
+
+    if let Some(x) = &ctx.auth_url {
+        println!("Auth URL:   {x}");
+    }
+
+    if let Some(x) = &ctx.pages_registry {
+        println!("Registry:   {x}");
+    }
+
+    if let Some(x) = &ctx.tunnel_domain {
+        println!("Tunnels:    {x}");
+    }
+
+    Ok(())
+}
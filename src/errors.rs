@@ -0,0 +1,314 @@
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+use tonic::{Code, Status};
+
+use crate::ports::schema::InvalidSpec;
+
+/// Failures that scripts may want to tell apart. Each category exits the
+/// process with its own code:
+///
+/// | code | category                              |
+/// |------|---------------------------------------|
+/// | 1    | any other error                       |
+/// | 2    | invalid command line usage            |
+/// | 3    | resource not found                    |
+/// | 4    | missing or expired credentials        |
+/// | 5    | permission denied                     |
+/// | 6    | invalid spec or request               |
+/// | 7    | quota exceeded                        |
+/// | 8    | network error or API unavailable      |
+/// | 9    | live ports differ from the manifests  |
+/// | 10   | config migrations pending             |
+/// | 130  | aborted by the user                   |
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    #[error("{0}")]
+    #[diagnostic(
+        code(dmtrctl::usage),
+        help("run the command with --help to see the arguments it accepts")
+    )]
+    Usage(String),
+
+    #[error("{0}")]
+    #[diagnostic(code(dmtrctl::not_found))]
+    NotFound(String),
+
+    #[error("not authenticated: {0}")]
+    #[diagnostic(
+        code(dmtrctl::unauthenticated),
+        help("check the api key of the current context, or run `dmtrctl init` again")
+    )]
+    Unauthenticated(String),
+
+    #[error("permission denied: {0}")]
+    #[diagnostic(code(dmtrctl::permission_denied))]
+    PermissionDenied(String),
+
+    #[error("invalid request: {0}")]
+    #[diagnostic(code(dmtrctl::invalid_spec))]
+    InvalidSpec(String),
+
+    #[error("quota exceeded: {0}")]
+    #[diagnostic(
+        code(dmtrctl::quota_exceeded),
+        help("delete what you no longer use, or contact support to raise your limits")
+    )]
+    QuotaExceeded(String),
+
+    #[error("can't reach the Demeter API: {0}")]
+    #[diagnostic(
+        code(dmtrctl::network),
+        help("check your connection, or raise the --timeout and --retries values")
+    )]
+    Network(String),
+
+    #[error("{0}")]
+    #[diagnostic(
+        code(dmtrctl::drift),
+        help("run `dmtrctl apply` to bring the ports in line with the manifests")
+    )]
+    Drift(String),
+
+    #[error("{0}")]
+    #[diagnostic(
+        code(dmtrctl::migrations_pending),
+        help("run `dmtrctl config migrate` to upgrade the config")
+    )]
+    MigrationsPending(String),
+
+    #[error("aborted")]
+    #[diagnostic(code(dmtrctl::aborted))]
+    Aborted,
+
+    #[error("{0}")]
+    #[diagnostic(code(dmtrctl::prompt))]
+    Prompt(inquire::InquireError),
+
+    #[error("rpc call failed with {code}: {message}")]
+    #[diagnostic(code(dmtrctl::rpc))]
+    Rpc { code: Code, message: String },
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::NotFound(_) => 3,
+            Error::Unauthenticated(_) => 4,
+            Error::PermissionDenied(_) => 5,
+            Error::InvalidSpec(_) => 6,
+            Error::QuotaExceeded(_) => 7,
+            Error::Network(_) => 8,
+            Error::Drift(_) => 9,
+            Error::MigrationsPending(_) => 10,
+            Error::Aborted => 130,
+            Error::Prompt(_) | Error::Rpc { .. } => 1,
+        }
+    }
+}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        let message = match status.message() {
+            "" => status.code().description().to_owned(),
+            x => x.to_owned(),
+        };
+
+        match status.code() {
+            Code::NotFound => Error::NotFound(message),
+            Code::Unauthenticated => Error::Unauthenticated(message),
+            Code::PermissionDenied => Error::PermissionDenied(message),
+            Code::InvalidArgument | Code::FailedPrecondition => Error::InvalidSpec(message),
+            Code::ResourceExhausted => Error::QuotaExceeded(message),
+            Code::Unavailable | Code::DeadlineExceeded => Error::Network(message),
+            Code::Cancelled if is_timeout(&status) => {
+                Error::Network("the request timed out".into())
+            }
+            code => Error::Rpc { code, message },
+        }
+    }
+}
+
+/// What tonic reports, as a cancelled status, when the channel timeout
+/// expires on our side. Its `TimeoutExpired` error isn't kept around, so the
+/// message is all there is to tell it apart from other cancellations.
+const CLIENT_TIMEOUT_MESSAGE: &str = "Timeout expired";
+
+/// Whether the call ran out of time, either on the server or because it hit
+/// the `--timeout` set on the channel.
+pub fn is_timeout(status: &Status) -> bool {
+    match status.code() {
+        Code::DeadlineExceeded => true,
+        Code::Cancelled => status.message() == CLIENT_TIMEOUT_MESSAGE,
+        _ => false,
+    }
+}
+
+/// An rpc failure classified into one of the `Error` categories, keeping the
+/// original gRPC status code for machine-readable output.
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: Code,
+    pub error: Error,
+}
+
+impl From<Status> for RpcError {
+    fn from(status: Status) -> Self {
+        Self {
+            code: status.code(),
+            error: Error::from(status),
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl Diagnostic for RpcError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.error.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.error.help()
+    }
+}
+
+impl From<inquire::InquireError> for Error {
+    fn from(err: inquire::InquireError) -> Self {
+        match err {
+            inquire::InquireError::OperationCanceled
+            | inquire::InquireError::OperationInterrupted => Error::Aborted,
+            other => Error::Prompt(other),
+        }
+    }
+}
+
+impl From<clap::Error> for Error {
+    fn from(err: clap::Error) -> Self {
+        // the first line holds the problem, the rest is usage help
+        let rendered = err.render().to_string();
+        let message = rendered.lines().next().unwrap_or_default();
+
+        Error::Usage(message.trim_start_matches("error: ").to_owned())
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Network(err.to_string())
+    }
+}
+
+/// Picks the exit code for an error that made it all the way up to main,
+/// looking through any context added along the way.
+pub fn exit_code(report: &miette::Report) -> i32 {
+    if let Some(err) = report.downcast_ref::<RpcError>() {
+        return err.error.exit_code();
+    }
+
+    if let Some(err) = report.downcast_ref::<Error>() {
+        return err.exit_code();
+    }
+
+    if report.downcast_ref::<InvalidSpec>().is_some() {
+        return 6;
+    }
+
+    1
+}
+
+#[derive(Serialize)]
+struct ErrorOutput {
+    code: Option<String>,
+    message: String,
+    causes: Vec<String>,
+    details: Vec<String>,
+    help: Option<String>,
+    grpc_status: Option<String>,
+    exit_code: i32,
+}
+
+/// Prints the error as a single json object, for tooling that can't parse
+/// the graphical report.
+pub fn print_json(report: &miette::Report) {
+    let details = report
+        .labels()
+        .map(|labels| labels.filter_map(|x| x.label().map(String::from)).collect())
+        .unwrap_or_default();
+
+    let output = ErrorOutput {
+        code: report.code().map(|x| x.to_string()),
+        message: report.to_string(),
+        causes: report.chain().skip(1).map(|x| x.to_string()).collect(),
+        details,
+        help: report.help().map(|x| x.to_string()),
+        grpc_status: report
+            .downcast_ref::<RpcError>()
+            .map(|x| format!("{:?}", x.code)),
+        exit_code: exit_code(report),
+    };
+
+    eprintln!("{}", serde_json::to_string(&output).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit_code_of(status: Status) -> i32 {
+        exit_code(&miette::Report::new(RpcError::from(status)))
+    }
+
+    #[test]
+    fn maps_statuses_to_exit_codes() {
+        assert_eq!(exit_code_of(Status::not_found("port not found")), 3);
+        assert_eq!(exit_code_of(Status::unauthenticated("bad key")), 4);
+        assert_eq!(exit_code_of(Status::permission_denied("nope")), 5);
+        assert_eq!(exit_code_of(Status::invalid_argument("bad spec")), 6);
+        assert_eq!(exit_code_of(Status::resource_exhausted("quota")), 7);
+        assert_eq!(exit_code_of(Status::unavailable("down")), 8);
+        assert_eq!(exit_code_of(Status::internal("boom")), 1);
+    }
+
+    #[test]
+    fn client_timeouts_are_network_errors() {
+        let status = Status::cancelled(CLIENT_TIMEOUT_MESSAGE);
+
+        assert!(matches!(Error::from(status), Error::Network(_)));
+        assert_eq!(exit_code_of(Status::cancelled(CLIENT_TIMEOUT_MESSAGE)), 8);
+        assert_eq!(exit_code_of(Status::deadline_exceeded("too slow")), 8);
+    }
+
+    #[test]
+    fn other_cancellations_are_rpc_errors() {
+        let status = Status::cancelled("the request was cancelled");
+
+        assert!(matches!(Error::from(status), Error::Rpc { .. }));
+    }
+
+    #[test]
+    fn usage_errors_keep_the_problem_only() {
+        let err = clap::Command::new("dmtrctl")
+            .try_get_matches_from(["dmtrctl", "--nope"])
+            .unwrap_err();
+
+        let report = miette::Report::new(Error::from(err));
+
+        assert_eq!(report.to_string(), "unexpected argument '--nope' found");
+        assert_eq!(exit_code(&report), 2);
+    }
+
+    #[test]
+    fn finds_errors_behind_added_context() {
+        let report = miette::Report::new(Error::Aborted).wrap_err("deleting port");
+
+        assert_eq!(exit_code(&report), 130);
+    }
+}
@@ -0,0 +1,127 @@
+use fs4::FileExt;
+use miette::{Context as _, IntoDiagnostic};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write as _,
+    path::Path,
+    sync::Mutex,
+};
+
+fn open_private(path: &Path, options: &mut OpenOptions) -> std::io::Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+        options.mode(0o600);
+    }
+
+    options.open(path)
+}
+
+/// Replaces the file atomically: the contents are written and synced to a
+/// temporary file next to it, which is then renamed into place. New files are
+/// only readable by the current user.
+pub fn write_private(path: &Path, contents: &[u8]) -> miette::Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|x| x.to_str())
+        .ok_or(miette::miette!("invalid file path {}", path.display()))?;
+
+    let tmp = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let written = open_private(
+        &tmp,
+        OpenOptions::new().write(true).create(true).truncate(true),
+    )
+    .and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    })
+    .and_then(|_| std::fs::rename(&tmp, path));
+
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(err)
+            .into_diagnostic()
+            .context(format!("writing {}", path.display()));
+    }
+
+    // persist the rename itself, best effort since not every platform allows
+    // syncing a directory
+    if let Some(dir) = path.parent() {
+        let _ = File::open(dir).and_then(|x| x.sync_all());
+    }
+
+    Ok(())
+}
+
+/// Prints a warning if the file can be read or written by users other than
+/// its owner.
+pub fn warn_if_exposed(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let exposed = std::fs::metadata(path)
+            .map(|x| x.permissions().mode() & 0o077 != 0)
+            .unwrap_or(false);
+
+        if exposed {
+            eprintln!(
+                "warning: {} is accessible by other users, run `chmod 600 {}` to restrict it",
+                path.display(),
+                path.display()
+            );
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// The lock file held by this process and how many guards are using it, so
+/// nested operations don't deadlock against their own lock.
+static HELD: Mutex<Option<(File, usize)>> = Mutex::new(None);
+
+/// Releases the advisory lock when the last guard is dropped.
+pub struct LockGuard;
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let mut held = HELD.lock().unwrap_or_else(|x| x.into_inner());
+
+        if let Some((file, count)) = held.as_mut() {
+            *count -= 1;
+
+            if *count == 0 {
+                let _ = file.unlock();
+                *held = None;
+            }
+        }
+    }
+}
+
+/// Takes an exclusive advisory lock on the given file, waiting for other
+/// processes to release it. Re-entrant within the same process.
+pub fn lock_exclusive(path: &Path) -> miette::Result<LockGuard> {
+    let mut held = HELD.lock().unwrap_or_else(|x| x.into_inner());
+
+    if let Some((_, count)) = held.as_mut() {
+        *count += 1;
+        return Ok(LockGuard);
+    }
+
+    let file = open_private(
+        path,
+        OpenOptions::new().write(true).create(true).truncate(false),
+    )
+    .into_diagnostic()
+    .context(format!("opening lock file {}", path.display()))?;
+
+    file.lock_exclusive()
+        .into_diagnostic()
+        .context(format!("locking {}", path.display()))?;
+
+    *held = Some((file, 1));
+
+    Ok(LockGuard)
+}
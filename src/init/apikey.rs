@@ -1,8 +1,6 @@
 use std::fmt::Display;
 
-use miette::IntoDiagnostic as _;
-
-use crate::rpc;
+use crate::{context::mask_secret, errors::Error, rpc};
 
 enum MaxKeysOptions {
     TryAgain,
@@ -18,18 +16,24 @@ impl Display for MaxKeysOptions {
     }
 }
 
-pub async fn define_api_key(access_token: &str, project_id: &str) -> miette::Result<String> {
-    println!("Setting up API key for project {}", project_id);
-    let api_key_result = rpc::projects::create_secret(access_token, project_id, "dmtrctl").await;
-    let mut api_key = api_key_result.unwrap_or_default();
+/// Returns the api key along with the id of its project secret, which is
+/// unknown when the key is entered manually.
+pub async fn define_api_key(
+    client: &rpc::DemeterClient,
+    project_id: &str,
+) -> miette::Result<(String, Option<String>)> {
+    eprintln!("Setting up API key for project {}", project_id);
 
-    if !api_key.is_empty() {
-        return Ok(api_key);
+    if let Ok(secret) = client.create_project_secret(project_id, "dmtrctl").await {
+        eprintln!("API key: {}", mask_secret(&secret.key));
+        return Ok((secret.key, Some(secret.id)));
     }
 
-    println!("We need to configure an API KEY for your project but you've already generated the max amount (2).");
-    println!("You can manage your existing key from the web console.");
-    println!();
+    let mut api_key = String::new();
+
+    eprintln!("We need to configure an API KEY for your project but you've already generated the max amount (2).");
+    eprintln!("You can revoke one with `dmtrctl keys delete <id>` or from the web console.");
+    eprintln!();
 
     while api_key.is_empty() {
         let next = inquire::Select::new(
@@ -37,11 +41,13 @@ pub async fn define_api_key(access_token: &str, project_id: &str) -> miette::Res
             vec![MaxKeysOptions::TryAgain, MaxKeysOptions::EnterManually],
         )
         .prompt()
-        .into_diagnostic()?;
+        .map_err(Error::from)?;
 
         match next {
             MaxKeysOptions::TryAgain => {
-                api_key = rpc::projects::create_secret(access_token, project_id, "dmtrctl").await?;
+                let secret = client.create_project_secret(project_id, "dmtrctl").await?;
+                eprintln!("API key: {}", mask_secret(&secret.key));
+                return Ok((secret.key, Some(secret.id)));
             }
             MaxKeysOptions::EnterManually => {
                 api_key = inquire::Password::new("API Key")
@@ -49,10 +55,10 @@ pub async fn define_api_key(access_token: &str, project_id: &str) -> miette::Res
                     .without_confirmation()
                     .with_help_message("eg: dmtr_apikey_xxxxxxxxxxxxx")
                     .prompt()
-                    .into_diagnostic()?;
+                    .map_err(Error::from)?;
             }
         }
     }
 
-    Ok(api_key)
+    Ok((api_key, None))
 }
@@ -3,7 +3,11 @@ use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, time::Duration};
 
-async fn find_login_url() -> (String, String) {
+use crate::errors::Error;
+
+pub const DEFAULT_AUTH_URL: &str = "https://txpipe.us.auth0.com";
+
+async fn find_login_url(auth_url: &str) -> miette::Result<(String, String)> {
     let mut params = HashMap::new();
     params.insert("client_id", "gpJ63MG5g1V1PKufM9WHGjjeAe7yCT8L");
     params.insert("scope", "profile openid email");
@@ -12,25 +16,24 @@ async fn find_login_url() -> (String, String) {
     let client = reqwest::Client::new();
 
     let req = client
-        .post("https://txpipe.us.auth0.com/oauth/device/code")
+        .post(format!("{auth_url}/oauth/device/code"))
         .header("content-type", "application/x-www-form-urlencoded")
         .form(&params)
         .build()
-        .unwrap();
+        .map_err(Error::from)?;
 
-    let res = client.execute(req).await.unwrap();
+    let res = client.execute(req).await.map_err(Error::from)?;
 
-    let body: serde_json::Value = res.json().await.unwrap();
+    let body: serde_json::Value = res.json().await.map_err(Error::from)?;
 
-    let uri = body
-        .get("verification_uri_complete")
-        .unwrap()
-        .as_str()
-        .unwrap();
+    let field = |name: &str| {
+        body.get(name)
+            .and_then(|x| x.as_str())
+            .map(String::from)
+            .ok_or(miette::miette!("login response is missing {name}"))
+    };
 
-    let device_code = body.get("device_code").unwrap().as_str().unwrap();
-
-    (uri.into(), device_code.into())
+    Ok((field("verification_uri_complete")?, field("device_code")?))
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -53,7 +56,7 @@ struct Auth0Success {
     token_type: String,
 }
 
-async fn poll_token(device_code: &str) -> (StatusCode, String) {
+async fn poll_token(auth_url: &str, device_code: &str) -> miette::Result<(StatusCode, String)> {
     let mut params = HashMap::new();
     params.insert("client_id", "gpJ63MG5g1V1PKufM9WHGjjeAe7yCT8L");
     params.insert("device_code", device_code);
@@ -62,39 +65,41 @@ async fn poll_token(device_code: &str) -> (StatusCode, String) {
     let client = reqwest::Client::new();
 
     let req = client
-        .post("https://txpipe.us.auth0.com/oauth/token")
+        .post(format!("{auth_url}/oauth/token"))
         .header("content-type", "application/x-www-form-urlencoded")
         .form(&params)
         .build()
-        .unwrap();
+        .map_err(Error::from)?;
 
-    let res = client.execute(req).await.unwrap();
+    let res = client.execute(req).await.map_err(Error::from)?;
 
     // print the text and return the statuscode
     let status_code = res.status();
-    let body: Auth0ResponseBody = res.json().await.unwrap();
+    let body: Auth0ResponseBody = res.json().await.map_err(Error::from)?;
 
     if status_code.is_success() {
         match body {
-            Auth0ResponseBody::Success(success) => return (status_code, success.access_token),
-            Auth0ResponseBody::Error(error) => return (status_code, error.error_description),
+            Auth0ResponseBody::Success(success) => return Ok((status_code, success.access_token)),
+            Auth0ResponseBody::Error(error) => return Ok((status_code, error.error_description)),
         }
     }
 
-    (status_code, "".into())
+    Ok((status_code, "".into()))
 }
 
-pub async fn run() -> miette::Result<String> {
-    let (url, device_code) = find_login_url().await;
-    println!("open this url in your browser to login:");
-    println!("{url}");
+pub async fn run(auth_url: &str) -> miette::Result<String> {
+    let auth_url = auth_url.trim_end_matches('/');
+
+    let (url, device_code) = find_login_url(auth_url).await?;
+    eprintln!("open this url in your browser to login:");
+    eprintln!("{url}");
 
     for _i in 0..20 {
         tokio::time::sleep(Duration::from_secs(5)).await;
-        let (status, access_token) = poll_token(&device_code).await;
+        let (status, access_token) = poll_token(auth_url, &device_code).await?;
 
         if status.is_success() {
-            println!("login successful!");
+            eprintln!("login successful!");
             return Ok(access_token);
         }
     }
@@ -1,18 +1,17 @@
-use miette::IntoDiagnostic;
-
-use crate::context::Context;
+use crate::{context::Context, errors::Error};
 
 pub async fn run(context: &Context, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
     println!("Setting up context for:\n");
     println!("  Project: {}", context.namespace.name);
-    println!("  API key: {}\n", context.auth.token);
+    let api_key = context.auth.resolve_token(dirs)?;
+    println!("  API key: {}\n", crate::context::mask_secret(&api_key));
 
     let is_default = inquire::Confirm::new("use as default context?")
         .with_help_message(
             "select this option to use this context when no explicit value is specified",
         )
         .prompt()
-        .into_diagnostic()?;
+        .map_err(Error::from)?;
 
     crate::context::overwrite_context(&context.namespace.name, context.clone(), is_default, dirs)?;
 
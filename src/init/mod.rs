@@ -1,13 +1,17 @@
-use crate::context::{load_config, Context};
+use crate::{
+    context::{load_config, Context},
+    errors::Error,
+    rpc,
+};
 use clap::Parser;
-use miette::{Context as _, IntoDiagnostic as _};
+use miette::Context as _;
 use std::fmt::Display;
 
 #[derive(Parser, Debug)]
 pub struct Args {}
 
-mod apikey;
-mod login;
+pub mod apikey;
+pub mod login;
 mod manual;
 pub mod project;
 
@@ -28,28 +32,69 @@ impl<'a> Display for ContextOption<'a> {
     }
 }
 
-pub async fn import_context(dirs: &crate::dirs::Dirs) -> miette::Result<Context> {
-    let access_token = login::run().await?;
+/// Logs in through the browser and returns a client that acts on behalf of
+/// the user, as needed to manage projects and their api keys.
+pub async fn login_client(cli: &crate::Cli) -> miette::Result<rpc::DemeterClient> {
+    let auth_url = cli
+        .context
+        .as_ref()
+        .and_then(|x| x.auth_url.as_deref())
+        .unwrap_or(login::DEFAULT_AUTH_URL);
 
-    let project = project::define_project(&access_token).await?;
+    let access_token = login::run(auth_url).await?;
 
-    let api_key = apikey::define_api_key(&access_token, &project.id).await?;
+    let client = cli
+        .client
+        .with_credential(rpc::auth::Credential::Auth0(access_token));
 
-    let ctx = crate::context::Context {
-        namespace: crate::context::Namespace::new(&project.namespace, Some(project.name)),
-        auth: crate::context::Auth::api_key(&api_key),
-    };
+    Ok(client)
+}
+
+/// Builds a context for the project with the api key set up for it.
+pub fn project_context(
+    cli: &crate::Cli,
+    project: &project::ProjectRef,
+    api_key: &str,
+    key_id: Option<String>,
+) -> Context {
+    let mut auth = crate::context::Auth::api_key(api_key);
+    auth.key_id = key_id;
+
+    let mut ctx = crate::context::Context::new(
+        crate::context::Namespace::new(&project.namespace, Some(project.name.clone())),
+        auth,
+    );
+
+    // the new context lives in the same cluster as the one we logged in from
+    if let Some(active) = cli.context.as_ref() {
+        ctx.rpc_url = active.rpc_url.clone();
+        ctx.auth_url = active.auth_url.clone();
+        ctx.pages_registry = active.pages_registry.clone();
+        ctx.tunnel_domain = active.tunnel_domain.clone();
+    }
+
+    ctx
+}
+
+pub async fn import_context(cli: &crate::Cli) -> miette::Result<Context> {
+    let client = login_client(cli).await?;
+
+    let project = project::define_project(&client).await?;
+
+    let (api_key, key_id) = apikey::define_api_key(&client, &project.id).await?;
+
+    let ctx = project_context(cli, &project, &api_key, key_id);
 
-    crate::context::overwrite_context(&project.namespace, ctx.clone(), false, dirs)?;
+    crate::context::overwrite_context(&project.namespace, ctx.clone(), false, &cli.dirs)?;
 
     Ok(ctx)
 }
 
-async fn define_context(dirs: &crate::dirs::Dirs) -> miette::Result<Context> {
-    let config = crate::context::load_config(dirs).context("loading config")?;
+async fn define_context(cli: &crate::Cli) -> miette::Result<Context> {
+    let config = crate::context::load_config(&cli.dirs).context("loading config")?;
 
     if config.contexts.is_empty() {
-        return import_context(dirs).await;
+        return import_context(cli).await;
     }
 
     let options = config
@@ -61,11 +106,11 @@ async fn define_context(dirs: &crate::dirs::Dirs) -> miette::Result<Context> {
 
     let selection = inquire::Select::new("Choose your context", options)
         .prompt()
-        .into_diagnostic()?;
+        .map_err(Error::from)?;
 
     match selection {
         ContextOption::Existing(x) => Ok(x.clone()),
-        ContextOption::ImportProject => import_context(dirs).await,
+        ContextOption::ImportProject => import_context(cli).await,
     }
 }
 
@@ -86,7 +131,7 @@ pub async fn run(_args: Args, cli: &crate::Cli) -> miette::Result<()> {
         }
     }
 
-    let ctx = define_context(&cli.dirs).await?;
+    let ctx = define_context(cli).await?;
 
     crate::context::set_default_context(&ctx.namespace.name, &cli.dirs)?;
 
@@ -1,9 +1,8 @@
 use std::fmt::Display;
 
 use dmtri::demeter::ops::v1alpha as proto;
-use miette::IntoDiagnostic;
 
-use crate::rpc;
+use crate::{errors::Error, rpc};
 
 pub fn parse_project_ref(id: String, namespace: String, name: String) -> ProjectRef {
     ProjectRef {
@@ -35,22 +34,22 @@ impl Display for ProjectOption {
     }
 }
 
-async fn new_project(access_token: &str) -> miette::Result<ProjectRef> {
+async fn new_project(client: &rpc::DemeterClient) -> miette::Result<ProjectRef> {
     let project_name = inquire::Text::new("Project name?")
         .with_help_message("Human readable name to identify the project")
         .prompt()
-        .into_diagnostic()?;
+        .map_err(Error::from)?;
 
-    let project = rpc::projects::create_project(access_token, &project_name).await?;
+    let project = client.create_project(&project_name).await?;
 
     Ok(project)
 }
 
-pub async fn define_project(access_token: &str) -> miette::Result<ProjectRef> {
-    let projects: Vec<proto::Project> = rpc::projects::find(access_token).await?;
+pub async fn define_project(client: &rpc::DemeterClient) -> miette::Result<ProjectRef> {
+    let projects: Vec<proto::Project> = client.find_projects().await?;
 
     if projects.is_empty() {
-        return new_project(access_token).await;
+        return new_project(client).await;
     }
 
     let options = projects
@@ -67,10 +66,10 @@ pub async fn define_project(access_token: &str) -> miette::Result<ProjectRef> {
 
     let selection = inquire::Select::new("Choose your project", options)
         .prompt()
-        .into_diagnostic()?;
+        .map_err(Error::from)?;
 
     match selection {
         ProjectOption::Existing(project) => Ok(project),
-        ProjectOption::New => new_project(access_token).await,
+        ProjectOption::New => new_project(client).await,
     }
 }
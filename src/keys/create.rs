@@ -0,0 +1,26 @@
+use clap::Parser;
+use miette::Context as _;
+
+use crate::context::extract_context_data;
+
+#[derive(Parser)]
+pub struct Args {
+    /// a name to tell the key apart from others
+    name: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    let secret = client
+        .create_project_secret(&project_id, &args.name)
+        .await
+        .context("creating the api key, if the project is at its limit revoke one with `dmtrctl keys delete <id>`")?;
+
+    println!("Created api key {} ({})", secret.name, secret.id);
+    println!("{}", secret.key);
+    println!();
+    println!("Store it somewhere safe, it won't be shown again");
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use clap::Parser;
+
+use crate::{context::extract_context_data, errors::Error};
+
+#[derive(Parser)]
+pub struct Args {
+    /// id of the api key, as shown by `keys list`
+    id: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let in_use =
+        cli.context.as_ref().and_then(|x| x.auth.key_id.as_deref()) == Some(args.id.as_str());
+
+    let msg = match in_use {
+        true => format!(
+            "{} is the api key of the current context, which will stop working. Do you want to proceed?",
+            args.id
+        ),
+        false => format!(
+            "You are about to revoke the api key {}. This action cannot be undone. Do you want to proceed?",
+            args.id
+        ),
+    };
+
+    let confirm = inquire::Confirm::new(&msg).prompt().map_err(Error::from)?;
+
+    if !confirm {
+        return Err(Error::Aborted.into());
+    }
+
+    let (client, _, _) = extract_context_data(cli).await?;
+
+    client.delete_project_secret(&args.id).await?;
+
+    println!("Revoked api key {}", args.id);
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use clap::Parser;
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, ContentArrangement, Table};
+
+use crate::context::extract_context_data;
+
+#[derive(Parser)]
+pub struct Args {}
+
+pub async fn run(_args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    let secrets = client.find_project_secrets(&project_id).await?;
+
+    if secrets.is_empty() {
+        println!("No api keys found, run `dmtrctl keys create <name>` to create one");
+        return Ok(());
+    }
+
+    let in_use = cli.context.as_ref().and_then(|x| x.auth.key_id.as_deref());
+
+    let mut table = Table::new();
+
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["In Use", "Id", "Name", "Created At"]);
+
+    for secret in secrets {
+        let marker = match in_use == Some(secret.id.as_str()) {
+            true => "*",
+            false => "",
+        };
+
+        table.add_row(vec![marker, &secret.id, &secret.name, &secret.created_at]);
+    }
+
+    println!("{table}");
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use clap::Parser;
+
+mod create;
+mod delete;
+mod list;
+mod rotate;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Parser)]
+pub enum Commands {
+    /// List the api keys of the current project
+    #[command(alias = "ls")]
+    List(list::Args),
+    /// Create a new api key for the current project
+    Create(create::Args),
+    /// Revoke an api key of the current project
+    #[command(alias = "rm")]
+    Delete(delete::Args),
+    /// Replace the api key of the current context with a new one
+    Rotate(rotate::Args),
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    match args.command {
+        Commands::List(x) => list::run(x, cli).await,
+        Commands::Create(x) => create::run(x, cli).await,
+        Commands::Delete(x) => delete::run(x, cli).await,
+        Commands::Rotate(x) => rotate::run(x, cli).await,
+    }
+}
@@ -0,0 +1,66 @@
+use clap::Parser;
+use miette::Context as _;
+
+use crate::{
+    context::{extract_context_data, overwrite_context, Auth, ContextSource},
+    rpc,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// name for the new key, defaults to the name of the current one
+    #[arg(long)]
+    name: Option<String>,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (ctx, origin) = match (&cli.context, &cli.context_origin) {
+        (Some(ctx), Some(origin)) if !matches!(origin.source, ContextSource::Credentials) => {
+            (ctx, origin)
+        }
+        _ => miette::bail!("rotating needs a saved context, use --context to pick one"),
+    };
+
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    let old_id = ctx.auth.key_id.clone();
+
+    let name = match (args.name, &old_id) {
+        (Some(x), _) => x,
+        (None, Some(id)) => client
+            .find_project_secrets(&project_id)
+            .await?
+            .into_iter()
+            .find(|x| &x.id == id)
+            .map(|x| x.name)
+            .unwrap_or("dmtrctl".to_owned()),
+        (None, None) => "dmtrctl".to_owned(),
+    };
+
+    let secret = client.create_project_secret(&project_id, &name).await
+        .context("creating the api key, if the project is at its limit revoke one with `dmtrctl keys delete <id>`")?;
+
+    // save the new key before revoking the old one, so a failure in between
+    // never leaves the context without a working key
+    let mut updated = ctx.clone();
+    updated.auth = Auth::api_key(&secret.key);
+    updated.auth.key_id = Some(secret.id.clone());
+
+    overwrite_context(&origin.name, updated, false, &cli.dirs)?;
+
+    println!("Context {} now uses api key {}", origin.name, secret.id);
+
+    let Some(old_id) = old_id else {
+        println!("The previous api key wasn't created by dmtrctl, revoke it with `dmtrctl keys delete <id>`");
+        return Ok(());
+    };
+
+    cli.client
+        .with_credential(rpc::auth::Credential::Secret((project_id, secret.key)))
+        .delete_project_secret(&old_id)
+        .await?;
+
+    println!("Revoked the previous api key {old_id}");
+
+    Ok(())
+}
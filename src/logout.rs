@@ -0,0 +1,126 @@
+use clap::Parser;
+use std::collections::HashMap;
+
+use crate::{
+    context::{load_config, logout_context, Context, ContextSource},
+    errors::Error,
+    init::login,
+    rpc,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// log out from every saved context, instead of the current one
+    #[arg(long, action)]
+    all: bool,
+
+    /// also revoke the api keys server-side, which requires logging in
+    #[arg(long, action)]
+    revoke: bool,
+}
+
+/// Access tokens by auth server, so contexts sharing one only log in once.
+/// They're kept in memory only, nothing from the login survives the process.
+type Logins = HashMap<String, String>;
+
+async fn revoke_key(
+    name: &str,
+    ctx: &Context,
+    logins: &mut Logins,
+    cli: &crate::Cli,
+) -> miette::Result<()> {
+    let Some(key_id) = ctx.auth.key_id.as_deref() else {
+        println!("The api key of context {name} wasn't created by dmtrctl, revoke it from the web console");
+        return Ok(());
+    };
+
+    let auth_url = ctx
+        .auth_url
+        .clone()
+        .unwrap_or(login::DEFAULT_AUTH_URL.to_owned());
+
+    let access_token = match logins.get(&auth_url) {
+        Some(x) => x.clone(),
+        None => {
+            let token = login::run(&auth_url).await?;
+            logins.insert(auth_url, token.clone());
+            token
+        }
+    };
+
+    // contexts may live in different clusters than the active one
+    let rpc_url = rpc::get_base_url(ctx.rpc_url.as_deref());
+
+    cli.client
+        .with_url(&rpc_url)?
+        .with_credential(rpc::auth::Credential::Auth0(access_token))
+        .delete_project_secret(key_id)
+        .await?;
+
+    println!("Revoked the api key of context {name}");
+
+    Ok(())
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let config = load_config(&cli.dirs)?;
+
+    let names: Vec<String> = match args.all {
+        true => config.contexts.keys().cloned().collect(),
+        false => {
+            // explicit credentials don't belong to any saved context
+            let origin = cli
+                .context_origin
+                .as_ref()
+                .filter(|x| !matches!(x.source, ContextSource::Credentials))
+                .ok_or(miette::miette!(
+                    "there's no saved context to log out from, use --context to pick one"
+                ))?;
+
+            vec![origin.name.clone()]
+        }
+    };
+
+    let mut logins = Logins::new();
+    let mut failed = vec![];
+
+    for name in names {
+        let ctx = config
+            .contexts
+            .get(&name)
+            .ok_or(Error::NotFound(format!("context {name} not found")))?;
+
+        if !ctx.auth.has_credentials() {
+            println!("Context {name} is already logged out");
+            continue;
+        }
+
+        // revoke first, so the key is kept if revoking fails
+        if args.revoke {
+            if let Err(err) = revoke_key(&name, ctx, &mut logins, cli).await {
+                if matches!(err.downcast_ref::<Error>(), Some(Error::Aborted)) {
+                    return Err(err);
+                }
+
+                eprintln!("Couldn't revoke the api key of context {name}: {err}");
+                failed.push(name);
+                continue;
+            }
+        }
+
+        logout_context(&name, &cli.dirs)?;
+
+        println!("Logged out from context {name}");
+    }
+
+    println!("Run `dmtrctl init` to log in again");
+
+    if !failed.is_empty() {
+        miette::bail!(
+            "couldn't revoke the api keys of {}, they're still logged in",
+            failed.join(", ")
+        );
+    }
+
+    Ok(())
+}
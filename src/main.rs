@@ -1,21 +1,48 @@
-use clap::{Parser, Subcommand};
+use clap::{parser::ValueSource, CommandFactory, FromArgMatches, Parser, Subcommand};
 use miette::Context as _;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::Level;
 use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
 
+mod apply;
+mod config;
 mod context;
+mod contexts;
 mod dirs;
+mod errors;
+mod files;
 mod init;
+mod keys;
+mod logout;
 mod pages;
 mod ports;
+mod projects;
 mod rpc;
+mod secrets;
+mod status;
 
 extern crate core;
 
+const EXIT_CODES: &str = "\
+Exit codes:
+  0    success
+  1    any other error
+  2    invalid command line usage
+  3    resource not found
+  4    missing or expired credentials
+  5    permission denied
+  6    invalid spec or request
+  7    quota exceeded
+  8    network error or API unavailable
+  9    live ports differ from the manifests
+  10   config migrations pending
+  130  aborted by the user";
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(after_long_help = EXIT_CODES)]
 pub struct Args {
     #[command(subcommand)]
     command: Commands,
@@ -32,6 +59,10 @@ pub struct Args {
     #[arg(short, long, global = true, env = "DMTR_CONTEXT")]
     context: Option<String>,
 
+    /// Whether the identity flags came from the command line or env vars
+    #[arg(skip)]
+    sources: ArgSources,
+
     /// The root location for dmtrctl files
     #[arg(short, long, global = true, env = "DMTR_ROOT_DIR")]
     root_dir: Option<PathBuf>,
@@ -40,6 +71,14 @@ pub struct Args {
     #[arg(short, long, global = true, action)]
     verbose: bool,
 
+    /// Timeout in seconds for connecting to and calling the Demeter API
+    #[arg(long, global = true, env = "DMTR_TIMEOUT")]
+    timeout: Option<u64>,
+
+    /// How many times read-only API calls are retried on transient errors
+    #[arg(long, global = true, env = "DMTR_RETRIES")]
+    retries: Option<u32>,
+
     /// Clear any previous config (use with caution)
     #[arg(long, action)]
     reset_config: bool,
@@ -50,48 +89,219 @@ pub enum Commands {
     /// Initialize your Demeter project
     Init(init::Args),
 
+    /// Remove the api key of a context, optionally revoking it
+    Logout(logout::Args),
+
+    /// Show the identity and endpoint in use, and check they work
+    #[command(alias = "whoami")]
+    Status(status::Args),
+
+    /// Manage the api keys of the current project
+    Keys(keys::Args),
+
     /// Interact with Demeter Pages
     Pages(pages::Args),
 
+    /// Manage your projects
+    Projects(projects::Args),
+
     /// Ports-specific commands
     Ports(ports::Args),
+
+    /// Apply port manifests to the current project
+    Apply(apply::Args),
+
+    /// Manage the saved contexts
+    Context(contexts::Args),
+
+    /// Maintain the dmtrctl config file
+    Config(config::Args),
+}
+
+impl Commands {
+    fn wants_json(&self) -> bool {
+        match self {
+            Commands::Ports(x) => x.wants_json(),
+            Commands::Projects(x) => x.wants_json(),
+            // the rest have no output format to pick
+            Commands::Init(_)
+            | Commands::Logout(_)
+            | Commands::Status(_)
+            | Commands::Keys(_)
+            | Commands::Pages(_)
+            | Commands::Apply(_)
+            | Commands::Context(_)
+            | Commands::Config(_) => false,
+        }
+    }
+}
+
+/// Where each of the identity flags was taken from, `None` when not given.
+#[derive(Debug, Clone, Default)]
+pub struct ArgSources {
+    pub namespace: Option<ValueSource>,
+    pub api_key: Option<ValueSource>,
+    pub context: Option<ValueSource>,
+}
+
+impl ArgSources {
+    fn from_matches(matches: &clap::ArgMatches) -> Self {
+        Self {
+            namespace: matches.value_source("namespace"),
+            api_key: matches.value_source("api_key"),
+            context: matches.value_source("context"),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Cli {
     pub dirs: dirs::Dirs,
     pub context: Option<context::Context>,
+    pub context_origin: Option<context::ContextOrigin>,
+    pub sources: ArgSources,
+    pub client: rpc::DemeterClient,
+}
+
+/// Whether the raw arguments ask for json output, for the errors raised
+/// before they can be parsed.
+fn argv_wants_json(argv: &[String]) -> bool {
+    let flag = argv
+        .windows(2)
+        .any(|x| matches!(x[0].as_str(), "-o" | "--output") && x[1] == "json");
+
+    flag || argv
+        .iter()
+        .any(|x| matches!(x.as_str(), "-ojson" | "-o=json" | "--output=json"))
 }
 
 #[tokio::main]
-async fn main() -> miette::Result<()> {
-    let args = Args::parse();
-    let dirs = dirs::Dirs::try_new(args.root_dir.as_deref())?;
+async fn main() {
+    let env_json = std::env::var("DMTR_ERROR_FORMAT").is_ok_and(|x| x == "json");
+    let argv: Vec<String> = std::env::args().collect();
 
-    if args.reset_config {
-        println!("clearing previous config files...");
-        crate::context::clear_config(&dirs).context("clearing previous config files")?;
-    }
+    let parsed = Args::command()
+        .try_get_matches_from(&argv)
+        .and_then(|matches| Ok((Args::from_arg_matches(&matches)?, matches)));
 
-    let context = context::infer_context(
-        args.context.as_deref(),
-        args.namespace.as_deref(),
-        args.api_key.as_deref(),
-        &dirs,
-    )
-    .await?;
+    let args = match parsed {
+        Ok((mut args, matches)) => {
+            args.sources = ArgSources::from_matches(&matches);
+            args
+        }
+        // --help and --version come through here too, and go to stdout
+        Err(err) if err.use_stderr() && (env_json || argv_wants_json(&argv)) => {
+            let report = miette::Report::new(errors::Error::from(err));
+            errors::print_json(&report);
+            std::process::exit(errors::exit_code(&report));
+        }
+        Err(err) => err.exit(),
+    };
 
-    let cli = Cli { context, dirs };
+    let json_errors = env_json || args.command.wants_json();
+
+    if let Err(report) = run(args).await {
+        match json_errors {
+            true => errors::print_json(&report),
+            false => eprintln!("Error: {report:?}"),
+        }
+
+        std::process::exit(errors::exit_code(&report));
+    }
+}
 
+async fn run(args: Args) -> miette::Result<()> {
     if args.verbose {
         tracing_subscriber::registry()
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
             .with(tracing_subscriber::filter::Targets::default().with_target("dmtr", Level::DEBUG))
             .init();
     }
 
+    let dirs = dirs::Dirs::try_new(args.root_dir.as_deref())?;
+
+    if args.reset_config {
+        println!("clearing previous config files...");
+        crate::context::clear_config(&dirs).context("clearing previous config files")?;
+    }
+
+    // loading the config migrates it, so the config command works on the raw
+    // file instead
+    let raw_config = matches!(args.command, Commands::Config(_));
+
+    // these commands are how a broken setup gets fixed, e.g. a project file
+    // pinning a context that doesn't exist, so they can't fail because of it
+    let repairs_setup = matches!(
+        args.command,
+        Commands::Init(_) | Commands::Logout(_) | Commands::Context(_)
+    );
+
+    let inferred = match raw_config {
+        true => None,
+        false => {
+            let inferred = context::infer_context(
+                args.context.as_deref(),
+                args.sources.context == Some(ValueSource::EnvVariable),
+                args.namespace.as_deref(),
+                args.api_key.as_deref(),
+                &dirs,
+            )
+            .await;
+
+            match inferred {
+                Ok(x) => x,
+                Err(err) if repairs_setup => {
+                    eprintln!("warning: {err}, continuing without a context");
+                    None
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    };
+
+    // flags and env vars take precedence over the config file
+    let rpc_config = match raw_config {
+        true => context::RpcConfig::default(),
+        false => context::load_config(&dirs)?.rpc.unwrap_or_default(),
+    };
+    let defaults = rpc::RpcOptions::default();
+
+    let rpc_options = rpc::RpcOptions {
+        timeout: args
+            .timeout
+            .or(rpc_config.timeout)
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.timeout),
+        retries: args
+            .retries
+            .or(rpc_config.retries)
+            .unwrap_or(defaults.retries),
+    };
+
+    let (context, context_origin) = inferred.unzip();
+
+    // each context may point to its own cluster
+    let rpc_url = rpc::get_base_url(context.as_ref().and_then(|x| x.rpc_url.as_deref()));
+    let client = rpc::DemeterClient::new(&rpc_url, rpc_options)?;
+
+    let cli = Cli {
+        context,
+        context_origin,
+        sources: args.sources,
+        dirs,
+        client,
+    };
+
     match args.command {
         Commands::Init(args) => init::run(args, &cli).await,
+        Commands::Logout(args) => logout::run(args, &cli).await,
+        Commands::Status(args) => status::run(args, &cli).await,
+        Commands::Keys(args) => keys::run(args, &cli).await,
         Commands::Pages(args) => pages::run(args, &cli).await,
+        Commands::Projects(args) => projects::run(args, &cli).await,
         Commands::Ports(args) => ports::run(args, &cli).await,
+        Commands::Apply(args) => apply::run(args, &cli).await,
+        Commands::Context(args) => contexts::run(args, &cli).await,
+        Commands::Config(args) => config::run(args, &cli).await,
     }
 }
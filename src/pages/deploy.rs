@@ -20,7 +20,10 @@ pub struct Args {
     registry_auth: String,
 }
 
+const DEFAULT_REGISTRY: &str = "ghcr.io/demeter-run";
+
 fn define_image_name(
+    registry: &str,
     namespace: &str,
     channel: Option<&str>,
     commit: Option<&str>,
@@ -28,8 +31,11 @@ fn define_image_name(
     let channel = channel.unwrap_or("main");
     let commit = commit.unwrap_or("latest");
     let raw = format!(
-        "ghcr.io/demeter-run/pages-{}-{}:{}",
-        namespace, channel, commit
+        "{}/pages-{}-{}:{}",
+        registry.trim_end_matches('/'),
+        namespace,
+        channel,
+        commit
     );
     ImageName::parse(&raw).into_diagnostic()
 }
@@ -45,6 +51,7 @@ pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
 
     let source = args.source.unwrap_or_else(|| Path::new("./dist").into());
     let name = define_image_name(
+        ctx.pages_registry.as_deref().unwrap_or(DEFAULT_REGISTRY),
         &ctx.namespace.name,
         args.channel.as_deref(),
         args.commit_hash.as_deref(),
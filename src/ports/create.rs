@@ -1,62 +1,155 @@
 use clap::Parser;
-use miette::IntoDiagnostic;
+use miette::{Context as _, IntoDiagnostic};
 
-use crate::{context::extract_context_data, rpc};
+use crate::{
+    context::extract_context_data,
+    errors::Error,
+    rpc::metadata::{ResourceMetadata, ResourceMetadataOption},
+};
+
+use super::{
+    format::{OutputArgs, OutputFormat},
+    manifest, schema,
+};
 
 #[derive(Parser)]
-pub struct Args {}
+pub struct Args {
+    /// the kind of resource to create (eg: CardanoNodePort)
+    #[arg(long)]
+    kind: Option<String>,
+
+    /// the preset option to use, by description or by index (starting at 1)
+    #[arg(long, requires = "kind", conflicts_with = "spec")]
+    option: Option<String>,
+
+    /// path to a json or yaml file with the resource spec, use - for stdin
+    #[arg(long, requires = "kind")]
+    spec: Option<String>,
 
-pub async fn run(_args: Args, cli: &crate::Cli) -> miette::Result<()> {
-    let (api_key, project_id, _) = extract_context_data(cli).await?;
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// Reads a resource spec from a json or yaml file, or from stdin when the
+/// path is `-`.
+pub fn load_spec(path: &str) -> miette::Result<serde_json::Value> {
+    let raw = manifest::read_input(path)?;
 
-    let metadata = rpc::metadata::find().await?;
+    if path.ends_with(".json") {
+        return serde_json::from_str(&raw)
+            .into_diagnostic()
+            .context("parsing json spec");
+    }
 
+    // yaml is a superset of json, so anything else goes through the yaml parser
+    serde_yaml::from_str(&raw)
+        .into_diagnostic()
+        .context("parsing yaml spec")
+}
+
+fn define_kind<'a>(
+    kind: Option<&str>,
+    metadata: &'a [ResourceMetadata],
+) -> miette::Result<&'a ResourceMetadata> {
     let resouce_kinds = metadata
         .iter()
         .map(|m| m.crd.spec.names.kind.clone())
         .collect::<Vec<String>>();
 
-    let kind_selected =
-        inquire::Select::new("What resource do want to create?", resouce_kinds.clone())
+    let kind_selected = match kind {
+        Some(kind) => kind.to_owned(),
+        None => inquire::Select::new("What resource do want to create?", resouce_kinds.clone())
             .with_page_size(resouce_kinds.len())
             .prompt()
-            .into_diagnostic()?;
+            .map_err(Error::from)?,
+    };
 
-    let resource_metadata = metadata
+    metadata
         .iter()
         .find(|m| m.crd.spec.names.kind == kind_selected)
-        .unwrap();
+        .ok_or(miette::miette!(
+            "unknown kind {}, available kinds are: {}",
+            kind_selected,
+            resouce_kinds.join(", ")
+        ))
+}
 
+fn define_option<'a>(
+    option: Option<&str>,
+    resource_metadata: &'a ResourceMetadata,
+) -> miette::Result<&'a ResourceMetadataOption> {
     let resource_options = resource_metadata
         .options
         .iter()
         .map(|o| o.description.clone())
         .collect::<Vec<String>>();
 
-    let option_selected = inquire::Select::new("Select an option", resource_options.clone())
-        .with_page_size(resource_options.len())
-        .prompt()
-        .into_diagnostic()?;
+    let option_selected = match option {
+        Some(option) => option.to_owned(),
+        None => inquire::Select::new("Select an option", resource_options.clone())
+            .with_page_size(resource_options.len())
+            .prompt()
+            .map_err(Error::from)?,
+    };
 
-    let resource_option_selected = resource_metadata
+    let by_description = resource_metadata
         .options
         .iter()
-        .find(|r| r.description == option_selected)
-        .unwrap();
+        .find(|r| r.description == option_selected);
 
-    let confirm = inquire::Confirm::new("Do you want to proceed?")
-        .prompt()
-        .into_diagnostic()?;
+    let by_index = || {
+        option_selected
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| resource_metadata.options.get(i))
+    };
 
-    if !confirm {
-        println!("Aborted");
-        return Ok(());
+    by_description.or_else(by_index).ok_or(miette::miette!(
+        "unknown option {}, available options are: {}",
+        option_selected,
+        resource_options.join(", ")
+    ))
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    let metadata = client.find_metadata().await?;
+
+    let resource_metadata = define_kind(args.kind.as_deref(), &metadata)?;
+    let kind_selected = resource_metadata.crd.spec.names.kind.clone();
+
+    let spec = match &args.spec {
+        Some(path) => load_spec(path)?,
+        None => define_option(args.option.as_deref(), resource_metadata)?
+            .spec
+            .clone(),
+    };
+
+    schema::validate(&resource_metadata.crd, &spec)?;
+
+    let unattended = args.kind.is_some() && (args.spec.is_some() || args.option.is_some());
+
+    if !unattended {
+        let confirm = inquire::Confirm::new("Do you want to proceed?")
+            .prompt()
+            .map_err(Error::from)?;
+
+        if !confirm {
+            return Err(Error::Aborted.into());
+        }
     }
 
-    let spec = resource_option_selected.spec.to_string();
-    let result = rpc::resources::create(&api_key, &project_id, &kind_selected, &spec).await?;
+    let spec = spec.to_string();
+    let result = client
+        .create_resource(&project_id, &kind_selected, &spec)
+        .await?;
 
-    println!("Port {}({}) created", result.kind, result.id);
+    match args.output.format {
+        OutputFormat::Table => println!("Port {}({}) created", result.kind, result.id),
+        _ => args.output.format.pretty_print_single(&result),
+    }
 
     Ok(())
 }
@@ -1,7 +1,6 @@
 use clap::Parser;
-use miette::IntoDiagnostic;
 
-use crate::{context::extract_context_data, rpc};
+use crate::{context::extract_context_data, errors::Error};
 
 #[derive(Parser)]
 pub struct Args {
@@ -20,18 +19,15 @@ pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
         args.id
     );
 
-    let confirm = inquire::Confirm::new(&msg).prompt().into_diagnostic()?;
+    let confirm = inquire::Confirm::new(&msg).prompt().map_err(Error::from)?;
 
     if !confirm {
-        println!("Aborted");
-        return Ok(());
+        return Err(Error::Aborted.into());
     }
 
-    let (api_key, project_id, _) = extract_context_data(cli).await?;
+    let (client, _, _) = extract_context_data(cli).await?;
 
-    rpc::resources::delete(&api_key, &project_id, &args.id)
-        .await
-        .unwrap();
+    client.delete_resource(&args.id).await?;
 
     println!("Successfully deleted port: {}", args.id);
     Ok(())
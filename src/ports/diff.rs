@@ -0,0 +1,189 @@
+use clap::Parser;
+use colored::Colorize;
+use miette::{Context as _, IntoDiagnostic};
+use serde_json::Value;
+use std::io::IsTerminal;
+
+use crate::{context::extract_context_data, errors::Error};
+
+use super::manifest;
+
+#[derive(Parser)]
+pub struct Args {
+    /// path to a json or yaml file with the port manifests, use - for stdin
+    #[arg(short, long)]
+    file: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Added(Value),
+    Removed(Value),
+    Modified(Value, Value),
+}
+
+fn collect_changes(path: &str, old: &Value, new: &Value, out: &mut Vec<(String, Change)>) {
+    if old == new {
+        return;
+    }
+
+    let (old_map, new_map) = match (old, new) {
+        (Value::Object(a), Value::Object(b)) => (a, b),
+        _ => {
+            out.push((path.into(), Change::Modified(old.clone(), new.clone())));
+            return;
+        }
+    };
+
+    let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let child = format!("{path}.{key}");
+
+        match (old_map.get(key), new_map.get(key)) {
+            (Some(a), Some(b)) => collect_changes(&child, a, b, out),
+            (Some(a), None) => out.push((child, Change::Removed(a.clone()))),
+            (None, Some(b)) => out.push((child, Change::Added(b.clone()))),
+            (None, None) => (),
+        }
+    }
+}
+
+fn print_changes(changes: &[(String, Change)]) {
+    for (path, change) in changes {
+        match change {
+            Change::Added(x) => println!("  {} {path}: {}", "+".green(), x.to_string().green()),
+            Change::Removed(x) => println!("  {} {path}: {}", "-".red(), x.to_string().red()),
+            Change::Modified(a, b) => println!(
+                "  {} {path}: {} → {}",
+                "~".yellow(),
+                a.to_string().red(),
+                b.to_string().green()
+            ),
+        }
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let _ctx = cli
+        .context
+        .as_ref()
+        .ok_or(miette::miette!("can't diff ports without a context"))?;
+
+    // keep escape codes out of pipes and files, NO_COLOR still applies on a terminal
+    if !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+
+    let manifests = manifest::load_manifests(&args.file)?;
+
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    let live = client.find_resources(&project_id).await?;
+    let targets = manifest::match_live(&manifests, &live)?;
+
+    let mut drifted = 0;
+
+    for (manifest, current) in manifests.iter().zip(targets) {
+        let current = match current {
+            Some(x) => x,
+            None => {
+                let name = manifest.name.as_deref().unwrap_or("<new>");
+                println!("{} {} {} (not found)", "+".green(), manifest.kind, name);
+                drifted += 1;
+                continue;
+            }
+        };
+
+        let current_spec: Value = serde_json::from_str(&current.spec)
+            .into_diagnostic()
+            .context(format!("parsing spec of port {}", current.name))?;
+
+        let desired = manifest.desired_spec(&current_spec);
+
+        let mut changes = vec![];
+        collect_changes("", &current_spec, &desired, &mut changes);
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        println!(
+            "{} {} {} ({})",
+            "~".yellow(),
+            current.kind,
+            current.name,
+            current.id
+        );
+        print_changes(&changes);
+        drifted += 1;
+    }
+
+    if drifted == 0 {
+        println!("No differences found");
+        return Ok(());
+    }
+
+    Err(Error::Drift(format!("{drifted} port(s) differ from the manifests")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn changes(old: Value, new: Value) -> Vec<(String, Change)> {
+        let mut out = vec![];
+        collect_changes("", &old, &new, &mut out);
+        out
+    }
+
+    #[test]
+    fn equal_specs_have_no_changes() {
+        let spec = json!({ "network": "mainnet", "labels": { "team": "infra" } });
+
+        assert!(changes(spec.clone(), spec).is_empty());
+    }
+
+    #[test]
+    fn lists_changes_by_sorted_path() {
+        let old = json!({ "network": "mainnet", "tier": 1, "labels": { "team": "infra" } });
+        let new = json!({ "network": "preprod", "labels": { "team": "infra", "env": "prod" }, "replicas": 2 });
+
+        assert_eq!(
+            changes(old, new),
+            vec![
+                (".labels.env".into(), Change::Added(json!("prod"))),
+                (
+                    ".network".into(),
+                    Change::Modified(json!("mainnet"), json!("preprod"))
+                ),
+                (".replicas".into(), Change::Added(json!(2))),
+                (".tier".into(), Change::Removed(json!(1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_and_type_changes_are_modified_whole() {
+        let old = json!({ "ports": [1, 2], "tier": { "level": 1 } });
+        let new = json!({ "ports": [1, 3], "tier": 1 });
+
+        assert_eq!(
+            changes(old, new),
+            vec![
+                (
+                    ".ports".into(),
+                    Change::Modified(json!([1, 2]), json!([1, 3]))
+                ),
+                (
+                    ".tier".into(),
+                    Change::Modified(json!({ "level": 1 }), json!(1))
+                ),
+            ]
+        );
+    }
+}
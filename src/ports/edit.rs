@@ -0,0 +1,127 @@
+use clap::Parser;
+use miette::{bail, Context as _, IntoDiagnostic};
+use std::path::Path;
+
+use crate::{context::extract_context_data, errors::Error};
+
+use super::{
+    format::{OutputArgs, OutputFormat},
+    patch, schema,
+};
+
+const EDIT_HEADER: &str = "\
+# Please edit the spec below. Lines beginning with '#' are ignored,
+# and an empty file will abort the edit.
+";
+
+#[derive(Parser)]
+pub struct Args {
+    /// the resource uuid
+    id: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+fn launch_editor(path: &Path) -> miette::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or("vi".into());
+
+    // editors are often configured with extra args (eg: "code --wait")
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .into_diagnostic()
+        .context(format!("launching editor {editor}"))?;
+
+    if !status.success() {
+        bail!("editor exited with {status}");
+    }
+
+    Ok(())
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (client, _, namespace) = extract_context_data(cli).await?;
+
+    let resource = client
+        .find_resources_by_id(&args.id)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(Error::NotFound(format!("port {} not found", args.id)))?;
+
+    let current: serde_json::Value = serde_json::from_str(&resource.spec)
+        .into_diagnostic()
+        .context("parsing current resource spec")?;
+
+    let yaml = serde_yaml::to_string(&current)
+        .into_diagnostic()
+        .context("serializing current spec")?;
+
+    let path = cli
+        .dirs
+        .ensure_tmp_dir(&namespace)?
+        .join(format!("{}.yaml", resource.id));
+
+    std::fs::write(&path, format!("{EDIT_HEADER}{yaml}"))
+        .into_diagnostic()
+        .context("writing temporary spec file")?;
+
+    launch_editor(&path)?;
+
+    let edited = std::fs::read_to_string(&path)
+        .into_diagnostic()
+        .context("reading edited spec file")?;
+
+    let is_empty = edited
+        .lines()
+        .all(|l| l.trim().is_empty() || l.trim_start().starts_with('#'));
+
+    if is_empty {
+        std::fs::remove_file(&path).into_diagnostic()?;
+        return Err(Error::Aborted.into());
+    }
+
+    let edited: serde_json::Value = serde_yaml::from_str(&edited)
+        .into_diagnostic()
+        .context(format!("parsing edited spec, saved at {}", path.display()))?;
+
+    if edited == current {
+        std::fs::remove_file(&path).into_diagnostic()?;
+        println!("Edit cancelled, no changes made");
+        return Ok(());
+    }
+
+    let metadata = client.find_metadata().await?;
+
+    if let Some(m) = metadata
+        .iter()
+        .find(|m| m.crd.spec.names.kind == resource.kind)
+    {
+        if let Err(err) = schema::validate(&m.crd, &edited) {
+            eprintln!("your changes were saved at {}", path.display());
+            return Err(err);
+        }
+    }
+
+    let spec_patch = patch::diff(&current, &edited);
+
+    let result = client
+        .update_resource(&args.id, &spec_patch.to_string())
+        .await?;
+
+    std::fs::remove_file(&path).into_diagnostic()?;
+
+    match args.output.format {
+        OutputFormat::Table => println!("Port {}({}) edited", result.kind, result.id),
+        _ => args.output.format.pretty_print_single(&result),
+    }
+
+    Ok(())
+}
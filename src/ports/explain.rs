@@ -0,0 +1,219 @@
+use clap::Parser;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+    JSONSchemaProps, JSONSchemaPropsOrArray, JSONSchemaPropsOrBool,
+};
+use miette::bail;
+
+use super::schema;
+
+#[derive(Parser)]
+pub struct Args {
+    /// the kind to explain, optionally followed by a field path within the
+    /// spec (eg: CardanoNodePort.network or CardanoNodePort.spec.network)
+    resource: String,
+
+    /// print the fields of every nested level as a tree
+    #[arg(long, action)]
+    recursive: bool,
+}
+
+/// Returns the schema that describes the values of a field: the items of an
+/// array or the values of a map, otherwise the field itself.
+fn inner_schema(schema: &JSONSchemaProps) -> &JSONSchemaProps {
+    if let Some(JSONSchemaPropsOrArray::Schema(items)) = &schema.items {
+        return inner_schema(items);
+    }
+
+    if schema.properties.is_none() {
+        if let Some(JSONSchemaPropsOrBool::Schema(values)) = &schema.additional_properties {
+            return inner_schema(values);
+        }
+    }
+
+    schema
+}
+
+fn type_label(schema: &JSONSchemaProps) -> String {
+    if schema.x_kubernetes_int_or_string.unwrap_or_default() {
+        return "IntOrString".into();
+    }
+
+    match schema.type_.as_deref() {
+        Some("array") => match &schema.items {
+            Some(JSONSchemaPropsOrArray::Schema(items)) => format!("[]{}", type_label(items)),
+            _ => "[]Object".into(),
+        },
+        Some("object") | None => match &schema.additional_properties {
+            Some(JSONSchemaPropsOrBool::Schema(values)) if schema.properties.is_none() => {
+                format!("map[string]{}", type_label(values))
+            }
+            _ => "Object".into(),
+        },
+        Some(x) => x.into(),
+    }
+}
+
+fn print_indented(text: &str, indent: usize) {
+    let pad = " ".repeat(indent);
+
+    for line in text.lines() {
+        println!("{pad}{line}");
+    }
+}
+
+fn print_constraints(schema: &JSONSchemaProps, indent: usize) {
+    let pad = " ".repeat(indent);
+
+    if let Some(values) = &schema.enum_ {
+        let values = values
+            .iter()
+            .map(|x| x.0.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        println!("{pad}ENUM: {values}");
+    }
+
+    if let Some(default) = &schema.default {
+        println!("{pad}DEFAULT: {}", default.0);
+    }
+}
+
+fn is_required(parent: &JSONSchemaProps, field: &str) -> bool {
+    parent.required.iter().flatten().any(|x| x == field)
+}
+
+fn print_fields(schema: &JSONSchemaProps) {
+    let properties = match &inner_schema(schema).properties {
+        Some(x) => x,
+        None => return,
+    };
+
+    println!("FIELDS:");
+
+    for (name, field) in properties {
+        let required = match is_required(inner_schema(schema), name) {
+            true => " -required-",
+            false => "",
+        };
+
+        println!("  {name}\t<{}>{required}", type_label(field));
+
+        match &field.description {
+            Some(description) => print_indented(description, 4),
+            None => println!("    <no description>"),
+        }
+
+        print_constraints(field, 4);
+        println!();
+    }
+}
+
+fn print_tree(schema: &JSONSchemaProps, indent: usize) {
+    let schema = inner_schema(schema);
+
+    let properties = match &schema.properties {
+        Some(x) => x,
+        None => return,
+    };
+
+    for (name, field) in properties {
+        let required = match is_required(schema, name) {
+            true => " -required-",
+            false => "",
+        };
+
+        println!(
+            "{}{name}\t<{}>{required}",
+            " ".repeat(indent),
+            type_label(field)
+        );
+
+        print_tree(field, indent + 3);
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let mut segments = args.resource.split('.').peekable();
+    let kind = segments.next().unwrap_or_default();
+
+    let metadata = cli.client.find_metadata().await?;
+
+    let resource_metadata = metadata
+        .iter()
+        .find(|m| {
+            let names = &m.crd.spec.names;
+            names.kind.eq_ignore_ascii_case(kind) || names.plural.eq_ignore_ascii_case(kind)
+        })
+        .ok_or(miette::miette!("unknown kind {kind}"))?;
+
+    let crd = &resource_metadata.crd;
+
+    let mut current = match schema::spec_schema(crd) {
+        Some(x) => x,
+        None => bail!("{} doesn't publish a schema", crd.spec.names.kind),
+    };
+
+    // kubectl paths start at the resource, so `Kind.spec.x` is `Kind.x`,
+    // unless the spec has a field named spec of its own
+    let spec_field = inner_schema(current)
+        .properties
+        .as_ref()
+        .is_some_and(|p| p.contains_key("spec"));
+
+    if segments.peek() == Some(&"spec") && !spec_field {
+        segments.next();
+    }
+
+    let mut field = None;
+
+    for segment in segments {
+        current = match inner_schema(current)
+            .properties
+            .as_ref()
+            .and_then(|p| p.get(segment))
+        {
+            Some(x) => x,
+            None => bail!("field {segment} does not exist in {}", args.resource),
+        };
+
+        field = Some(segment);
+    }
+
+    let version = crd
+        .spec
+        .versions
+        .iter()
+        .find(|v| v.storage)
+        .map(|v| v.name.as_str())
+        .unwrap_or_default();
+
+    println!("KIND:     {}", crd.spec.names.kind);
+    println!("VERSION:  {}/{}", crd.spec.group, version);
+    println!();
+
+    match field {
+        Some(field) => println!("FIELD:    {field} <{}>", type_label(current)),
+        None => println!("RESOURCE: spec <{}>", type_label(current)),
+    }
+    println!();
+
+    println!("DESCRIPTION:");
+    match &current.description {
+        Some(description) => print_indented(description, 4),
+        None => println!("    <empty>"),
+    }
+    print_constraints(current, 4);
+    println!();
+
+    if args.recursive {
+        if inner_schema(current).properties.is_some() {
+            println!("FIELDS:");
+            print_tree(current, 2);
+        }
+    } else {
+        print_fields(current);
+    }
+
+    Ok(())
+}
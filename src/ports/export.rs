@@ -0,0 +1,72 @@
+use clap::Parser;
+use miette::{Context as _, IntoDiagnostic};
+use std::path::PathBuf;
+
+use crate::{context::extract_context_data, errors::Error};
+
+use super::manifest::{Manifest, ManifestFormat};
+
+#[derive(Parser)]
+pub struct Args {
+    /// the uuids of the ports to export
+    #[arg(required_unless_present = "all")]
+    ids: Vec<String>,
+
+    /// export every port of the project
+    #[arg(long, action, conflicts_with = "ids")]
+    all: bool,
+
+    /// the format of the generated manifests
+    #[arg(long, default_value_t, value_enum)]
+    pub format: ManifestFormat,
+
+    /// write the manifests to a file instead of stdout
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let _ctx = cli
+        .context
+        .as_ref()
+        .ok_or(miette::miette!("can't export ports without a context"))?;
+
+    let (client, project_id, _) = extract_context_data(cli).await?;
+    let resources = client.find_resources(&project_id).await?;
+
+    let selected = match args.all {
+        true => resources,
+        false => {
+            for id in &args.ids {
+                if !resources.iter().any(|r| &r.id == id) {
+                    return Err(Error::NotFound(format!("port {id} not found")).into());
+                }
+            }
+
+            resources
+                .into_iter()
+                .filter(|r| args.ids.contains(&r.id))
+                .collect()
+        }
+    };
+
+    let manifests = selected
+        .iter()
+        .map(Manifest::from_resource)
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    let output = args.format.serialize(&manifests)?;
+
+    match args.file {
+        Some(path) => {
+            std::fs::write(&path, output)
+                .into_diagnostic()
+                .context("writing manifests file")?;
+
+            eprintln!("Exported {} port(s) to {}", manifests.len(), path.display());
+        }
+        None => println!("{output}"),
+    }
+
+    Ok(())
+}
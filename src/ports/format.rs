@@ -2,34 +2,246 @@ use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{ContentArrangement, Table};
 use dmtri::demeter::ops::v1alpha::Resource;
-use miette::IntoDiagnostic;
 use serde::Serialize;
+use std::str::FromStr;
 
-#[derive(clap::ValueEnum, Clone, Default, Debug, Serialize)]
-#[serde(rename_all = "lowercase")]
+use crate::rpc::metadata::ResourceMetadata;
+
+use super::jsonpath::JsonPath;
+
+#[derive(Clone, Default, Debug)]
 pub enum OutputFormat {
     #[default]
     Table,
     Json,
+    Yaml,
+    JsonPath(JsonPath),
+    CustomColumns(Vec<(String, JsonPath)>),
+}
+
+/// The `-o` flag of the commands that print ports or projects.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OutputArgs {
+    /// output format: table, json, yaml, jsonpath=<expr> or
+    /// custom-columns=<NAME:expr,...>
+    #[arg(short = 'o', long = "output", value_name = "OUTPUT", default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(expr) = s.strip_prefix("jsonpath=") {
+            return Ok(OutputFormat::JsonPath(expr.parse()?));
+        }
+
+        if let Some(spec) = s.strip_prefix("custom-columns=") {
+            // commas inside filter literals don't start a new column
+            let columns = super::jsonpath::split_unquoted(spec, ',')
+                .into_iter()
+                .map(|column| match column.split_once(':') {
+                    Some((name, expr)) => Ok((name.to_owned(), expr.parse()?)),
+                    None => Err(format!("invalid column {column}, expected NAME:<jsonpath>")),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            return Ok(OutputFormat::CustomColumns(columns));
+        }
+
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            x => Err(format!(
+                "unknown output format {x}, expected table, json, yaml, jsonpath=<expr> or custom-columns=<spec>"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Table => f.write_str("table"),
+            OutputFormat::Json => f.write_str("json"),
+            OutputFormat::Yaml => f.write_str("yaml"),
+            OutputFormat::JsonPath(x) => write!(f, "jsonpath={x}"),
+            OutputFormat::CustomColumns(columns) => {
+                let spec = columns
+                    .iter()
+                    .map(|(name, expr)| format!("{name}:{expr}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                write!(f, "custom-columns={spec}")
+            }
+        }
+    }
 }
 
 impl OutputFormat {
     pub fn pretty_print(&self, resources: Vec<Resource>) {
+        self.pretty_print_page(resources, 1);
+    }
+
+    /// Like `pretty_print`, for a page of a longer list, `first` being the
+    /// position of its first item so that table rows keep counting up.
+    pub fn pretty_print_page(&self, resources: Vec<Resource>, first: usize) {
+        let views: Vec<ResourceView> = resources.iter().map(ResourceView::from).collect();
+
         match self {
-            OutputFormat::Table => pretty_print_resource_table(resources),
-            OutputFormat::Json => pretty_print_resource_json(resources),
+            OutputFormat::Table => pretty_print_resource_table(&views, first),
+            OutputFormat::Json => pretty_print_json(&views),
+            OutputFormat::Yaml => pretty_print_yaml(&views),
+            OutputFormat::JsonPath(expr) => print_jsonpath(expr, &views),
+            OutputFormat::CustomColumns(columns) => print_custom_columns(columns, &views),
         }
     }
 
     pub fn pretty_print_single(&self, resource: &Resource) {
+        let view = ResourceView::from(resource);
+
         match self {
-            OutputFormat::Table => pretty_print_resource_detail_table(resource),
-            OutputFormat::Json => pretty_print_resource_detail_json(resource),
+            OutputFormat::Table => pretty_print_resource_detail_table(&view),
+            OutputFormat::Json => pretty_print_json(&view),
+            OutputFormat::Yaml => pretty_print_yaml(&view),
+            OutputFormat::JsonPath(expr) => print_jsonpath(expr, &[view]),
+            OutputFormat::CustomColumns(columns) => print_custom_columns(columns, &[view]),
+        }
+    }
+
+    pub fn pretty_print_kinds(&self, metadata: &[ResourceMetadata]) {
+        let views: Vec<KindView> = metadata.iter().map(KindView::from).collect();
+
+        match self {
+            OutputFormat::Table => pretty_print_kinds_table(&views),
+            OutputFormat::Json => pretty_print_json(&views),
+            OutputFormat::Yaml => pretty_print_yaml(&views),
+            OutputFormat::JsonPath(expr) => print_jsonpath(expr, &views),
+            OutputFormat::CustomColumns(columns) => print_custom_columns(columns, &views),
+        }
+    }
+}
+
+/// Decodes an embedded json string, keeping the raw value if it isn't valid
+/// json.
+fn decode_embedded(raw: &str) -> serde_json::Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::from(raw))
+}
+
+/// The shape in which a resource is presented by every output format, with
+/// its spec and annotations decoded into structured values.
+#[derive(Serialize)]
+pub struct ResourceView {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub created_at: String,
+    pub spec: serde_json::Value,
+    pub annotations: serde_json::Value,
+}
+
+impl From<&Resource> for ResourceView {
+    fn from(resource: &Resource) -> Self {
+        Self {
+            id: resource.id.clone(),
+            name: resource.name.clone(),
+            kind: resource.kind.clone(),
+            created_at: resource.created_at.clone(),
+            spec: decode_embedded(&resource.spec),
+            annotations: resource
+                .annotations
+                .as_deref()
+                .map(decode_embedded)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct KindOptionView {
+    pub index: usize,
+    pub description: String,
+    pub spec: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct KindView {
+    pub kind: String,
+    pub options: Vec<KindOptionView>,
+}
+
+impl From<&ResourceMetadata> for KindView {
+    fn from(metadata: &ResourceMetadata) -> Self {
+        Self {
+            kind: metadata.crd.spec.names.kind.clone(),
+            options: metadata
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, o)| KindOptionView {
+                    index: i + 1,
+                    description: o.description.clone(),
+                    spec: o.spec.clone(),
+                })
+                .collect(),
         }
     }
 }
 
-pub fn pretty_print_resource_table(resources: Vec<Resource>) {
+pub fn pretty_print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+pub fn pretty_print_yaml<T: Serialize>(value: &T) {
+    print!("{}", serde_yaml::to_string(value).unwrap());
+}
+
+/// Evaluates the expression against each item and prints one line per item.
+pub fn print_jsonpath<T: Serialize>(expr: &JsonPath, items: &[T]) {
+    for item in items {
+        let value = serde_json::to_value(item).unwrap();
+        println!("{}", expr.render(&value));
+    }
+}
+
+/// Prints one aligned, plain-text row per item, with a column for each
+/// expression.
+pub fn print_custom_columns<T: Serialize>(columns: &[(String, JsonPath)], items: &[T]) {
+    let mut rows: Vec<Vec<String>> = vec![columns.iter().map(|(name, _)| name.clone()).collect()];
+
+    for item in items {
+        let value = serde_json::to_value(item).unwrap();
+
+        let row = columns
+            .iter()
+            .map(|(_, expr)| match expr.render(&value) {
+                x if x.is_empty() => "<none>".to_owned(),
+                x => x,
+            })
+            .collect();
+
+        rows.push(row);
+    }
+
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("   ");
+
+        println!("{}", line.trim_end());
+    }
+}
+
+pub fn pretty_print_resource_table(resources: &[ResourceView], first: usize) {
     let mut table = Table::new();
 
     table
@@ -40,7 +252,7 @@ pub fn pretty_print_resource_table(resources: Vec<Resource>) {
 
     for (i, resource) in resources.iter().enumerate() {
         table.add_row(vec![
-            &(i + 1).to_string(),
+            &(first + i).to_string(),
             &resource.id,
             &resource.name,
             &resource.kind,
@@ -51,83 +263,19 @@ pub fn pretty_print_resource_table(resources: Vec<Resource>) {
     println!("{table}");
 }
 
-pub fn pretty_print_resource_json(resources: Vec<Resource>) {
-    let mut collector = vec![];
-    for resource in resources {
-        collector.push(serde_json::Map::from_iter(vec![
-            ("id".to_string(), serde_json::Value::from(resource.id)),
-            ("name".to_string(), serde_json::Value::from(resource.name)),
-            ("kind".to_string(), serde_json::Value::from(resource.kind)),
-            (
-                "created_at".to_string(),
-                serde_json::Value::from(resource.created_at),
-            ),
-            (
-                "spec".to_string(),
-                serde_json::from_str::<serde_json::Value>(&resource.spec).unwrap(),
-            ),
-            (
-                "annotations".to_string(),
-                serde_json::from_str::<serde_json::Value>(
-                    &resource.annotations.unwrap_or_default(),
-                )
-                .unwrap(),
-            ),
-        ]))
-    }
-    println!("{}", serde_json::to_string_pretty(&collector).unwrap());
-}
-
-pub fn pretty_print_resource_detail_json(resource: &Resource) {
-    println!(
-        "{}",
-        serde_json::to_string_pretty(
-            &(serde_json::Map::from_iter(vec![
-                (
-                    "id".to_string(),
-                    serde_json::Value::from(resource.id.clone())
-                ),
-                (
-                    "name".to_string(),
-                    serde_json::Value::from(resource.name.clone())
-                ),
-                (
-                    "kind".to_string(),
-                    serde_json::Value::from(resource.kind.clone())
-                ),
-                (
-                    "created_at".to_string(),
-                    serde_json::Value::from(resource.created_at.clone()),
-                ),
-                (
-                    "spec".to_string(),
-                    serde_json::from_str::<serde_json::Value>(&resource.spec).unwrap(),
-                ),
-                (
-                    "annotations".to_string(),
-                    serde_json::from_str::<serde_json::Value>(
-                        &resource.annotations.clone().unwrap_or_default(),
-                    )
-                    .unwrap(),
-                ),
-            ]))
-        )
-        .unwrap()
-    );
-}
-
-pub fn pretty_print_resource_detail_table(resource: &Resource) {
+pub fn pretty_print_resource_detail_table(resource: &ResourceView) {
     let mut table = Table::new();
 
-    let annotations = serde_json::from_str::<serde_json::Value>(
-        &resource.annotations.clone().unwrap_or_default(),
-    )
-    .unwrap();
+    let annotations = resource.annotations.as_array().cloned().unwrap_or_default();
+
     let mut annotations_headers: Vec<String> = annotations
-        .as_array()
-        .unwrap()
         .iter()
-        .map(|v| v.get("label").unwrap().as_str().unwrap_or_default().into())
+        .map(|v| {
+            v.get("label")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default()
+                .into()
+        })
         .collect();
 
     let mut headers = vec![String::from("Name")];
@@ -141,23 +289,75 @@ pub fn pretty_print_resource_detail_table(resource: &Resource) {
 
     let mut values: Vec<String> = vec![resource.name.clone()];
 
-    if let Some(annotations) = &resource.annotations {
-        let annotations: serde_json::Value =
-            serde_json::from_str(annotations).into_diagnostic().unwrap();
-
-        for value in annotations.as_array().unwrap().iter() {
-            values.push(
-                value
-                    .get("value")
-                    .unwrap()
-                    .as_str()
-                    .unwrap_or_default()
-                    .into(),
-            );
-        }
+    for value in annotations.iter() {
+        values.push(
+            value
+                .get("value")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default()
+                .into(),
+        );
     }
 
     table.add_row(values);
 
     println!("{table}");
 }
+
+pub fn pretty_print_kinds_table(kinds: &[KindView]) {
+    let mut table = Table::new();
+
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Kind", "", "Option", "Spec"]);
+
+    for kind in kinds {
+        if kind.options.is_empty() {
+            table.add_row(vec![&kind.kind, "", "", ""]);
+        }
+
+        for option in kind.options.iter() {
+            table.add_row(vec![
+                &kind.kind,
+                &option.index.to_string(),
+                &option.description,
+                &option.spec.to_string(),
+            ]);
+        }
+    }
+
+    println!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_custom_columns_with_commas_in_filters() {
+        let format: OutputFormat =
+            "custom-columns=NAME:.name,ENDPOINT:.annotations[?(@.label=='a,b')].value"
+                .parse()
+                .unwrap();
+
+        let OutputFormat::CustomColumns(columns) = format else {
+            panic!("expected custom columns");
+        };
+
+        let names: Vec<_> = columns.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["NAME", "ENDPOINT"]);
+        assert_eq!(
+            columns[1].1.to_string(),
+            ".annotations[?(@.label=='a,b')].value"
+        );
+    }
+
+    #[test]
+    fn rejects_columns_without_name() {
+        assert!("custom-columns=NAME:.name,.kind"
+            .parse::<OutputFormat>()
+            .is_err());
+    }
+}
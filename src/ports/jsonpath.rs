@@ -0,0 +1,354 @@
+use serde_json::Value;
+
+/// A small subset of JSONPath, modeled on what kubectl accepts: dotted
+/// fields, indexes, wildcards and equality filters (eg:
+/// `{.annotations[?(@.label=="Endpoint")].value}`).
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    Wildcard,
+    Filter {
+        path: Vec<Segment>,
+        negate: bool,
+        value: Value,
+    },
+}
+
+fn parse_literal(raw: &str) -> Result<Value, String> {
+    let raw = raw.trim();
+
+    if let Some(inner) = raw.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')) {
+        return Ok(Value::from(inner));
+    }
+
+    serde_json::from_str(raw).map_err(|_| format!("invalid literal {raw} in filter"))
+}
+
+/// Calls `f` with the position of each char that isn't part of a quoted
+/// literal, stopping as soon as it returns true.
+fn scan_unquoted(raw: &str, mut f: impl FnMut(usize, char) -> bool) {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in raw.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if f(i, c) => return,
+            None => (),
+        }
+    }
+}
+
+/// Splits on every `separator` that's outside quotes and brackets, so that
+/// filters can hold it in their literals.
+pub fn split_unquoted(raw: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    scan_unquoted(raw, |i, c| {
+        match c {
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => depth -= 1,
+            x if x == separator && depth == 0 => {
+                parts.push(&raw[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => (),
+        }
+
+        false
+    });
+
+    parts.push(&raw[start..]);
+    parts
+}
+
+fn parse_filter(raw: &str) -> Result<Segment, String> {
+    // the first operator outside quotes, literals may contain either
+    let mut operator = None;
+
+    scan_unquoted(raw, |i, c| {
+        if (c == '=' || c == '!') && raw[i + 1..].starts_with('=') {
+            operator = Some((i, c == '!'));
+        }
+
+        operator.is_some()
+    });
+
+    let (left, right, negate) = match operator {
+        Some((i, negate)) => (&raw[..i], &raw[i + 2..], negate),
+        None => return Err(format!("unsupported filter {raw}, expected == or !=")),
+    };
+
+    let left = left
+        .trim()
+        .strip_prefix('@')
+        .ok_or(format!("filter {raw} must start with @"))?;
+
+    Ok(Segment::Filter {
+        path: parse_segments(left)?,
+        negate,
+        value: parse_literal(right)?,
+    })
+}
+
+fn parse_bracket(raw: &str) -> Result<Segment, String> {
+    let raw = raw.trim();
+
+    if raw == "*" {
+        return Ok(Segment::Wildcard);
+    }
+
+    if let Some(filter) = raw.strip_prefix("?(").and_then(|x| x.strip_suffix(')')) {
+        return parse_filter(filter);
+    }
+
+    if let Ok(index) = raw.parse::<usize>() {
+        return Ok(Segment::Index(index));
+    }
+
+    match parse_literal(raw)? {
+        Value::String(x) => Ok(Segment::Field(x)),
+        _ => Err(format!("unsupported index [{raw}]")),
+    }
+}
+
+fn parse_segments(raw: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut rest = raw.trim();
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            // filters can contain brackets of their own, so they close on ")]"
+            let end = match inner.starts_with("?(") {
+                true => inner.find(")]").map(|x| x + 1),
+                false => inner.find(']'),
+            }
+            .ok_or(format!("unclosed bracket in {raw}"))?;
+
+            segments.push(parse_bracket(&inner[..end])?);
+            rest = &inner[end + 1..];
+            continue;
+        }
+
+        let field = rest.strip_prefix('.').unwrap_or(rest);
+        let end = field.find(['.', '[']).unwrap_or(field.len());
+
+        match &field[..end] {
+            "" if segments.is_empty() && end == field.len() => (),
+            "" => return Err(format!("empty field name in {raw}")),
+            "*" => segments.push(Segment::Wildcard),
+            x => segments.push(Segment::Field(x.to_owned())),
+        }
+
+        rest = &field[end..];
+    }
+
+    Ok(segments)
+}
+
+impl std::str::FromStr for JsonPath {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut expr = raw.trim();
+
+        if let Some(inner) = expr.strip_prefix('{').and_then(|x| x.strip_suffix('}')) {
+            expr = inner;
+        }
+
+        let expr = expr.strip_prefix('$').unwrap_or(expr);
+
+        Ok(Self {
+            raw: raw.to_owned(),
+            segments: parse_segments(expr)?,
+        })
+    }
+}
+
+impl std::fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(x) => x.iter().collect(),
+        Value::Object(x) => x.values().collect(),
+        _ => vec![],
+    }
+}
+
+fn select_segments<'a>(value: &'a Value, segments: &[Segment]) -> Vec<&'a Value> {
+    let mut current = vec![value];
+
+    for segment in segments {
+        current = current
+            .into_iter()
+            .flat_map(|v| match segment {
+                Segment::Field(name) => v.get(name).into_iter().collect(),
+                Segment::Index(i) => v.get(*i).into_iter().collect(),
+                Segment::Wildcard => children(v),
+                Segment::Filter {
+                    path,
+                    negate,
+                    value,
+                } => children(v)
+                    .into_iter()
+                    .filter(|item| {
+                        let found = select_segments(item, path).into_iter().any(|x| x == value);
+                        found != *negate
+                    })
+                    .collect(),
+            })
+            .collect();
+    }
+
+    current
+}
+
+impl JsonPath {
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        select_segments(value, &self.segments)
+    }
+
+    /// Evaluates the expression and renders the matches as plain text, the
+    /// way kubectl does: strings unquoted, everything else as json.
+    pub fn render(&self, value: &Value) -> String {
+        self.select(value)
+            .into_iter()
+            .map(|x| match x {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn render(expr: &str, value: &Value) -> String {
+        expr.parse::<JsonPath>().unwrap().render(value)
+    }
+
+    fn port() -> Value {
+        json!({
+            "name": "mainnet-node",
+            "spec": { "network": "mainnet", "throughputTier": 1 },
+            "annotations": [
+                { "label": "Network", "value": "mainnet" },
+                { "label": "Endpoint", "value": "node.demeter.run" },
+            ],
+        })
+    }
+
+    #[test]
+    fn selects_dotted_fields() {
+        assert_eq!(render("{.spec.network}", &port()), "mainnet");
+        assert_eq!(render(".spec.throughputTier", &port()), "1");
+        assert_eq!(render("$.name", &port()), "mainnet-node");
+    }
+
+    #[test]
+    fn empty_expression_selects_the_root() {
+        let value = json!({ "a": 1 });
+
+        assert_eq!(render("{}", &value), r#"{"a":1}"#);
+        assert_eq!(render("{.}", &value), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn selects_indexes_and_wildcards() {
+        assert_eq!(render(".annotations[1].label", &port()), "Endpoint");
+        assert_eq!(render(".annotations[*].label", &port()), "Network Endpoint");
+        assert_eq!(render(".spec['network']", &port()), "mainnet");
+        assert_eq!(render(".annotations[5].label", &port()), "");
+    }
+
+    #[test]
+    fn filters_by_equality() {
+        assert_eq!(
+            render(r#"{.annotations[?(@.label=="Endpoint")].value}"#, &port()),
+            "node.demeter.run"
+        );
+        assert_eq!(
+            render(".annotations[?(@.label != 'Endpoint')].value", &port()),
+            "mainnet"
+        );
+        assert_eq!(
+            render(".annotations[?(@.label=='Missing')].value", &port()),
+            ""
+        );
+    }
+
+    #[test]
+    fn operators_inside_literals_are_part_of_the_literal() {
+        let value = json!([
+            { "label": "a!=b", "value": 1 },
+            { "label": "a==b", "value": 2 },
+            { "label": "c", "value": 3 },
+        ]);
+
+        assert_eq!(render(r#"[?(@.label=="a!=b")].value"#, &value), "1");
+        assert_eq!(render("[?(@.label=='a==b')].value", &value), "2");
+        assert_eq!(render("[?(@.label!='a==b')].value", &value), "1 3");
+        assert_eq!(render(r#"[?(@.label=="say \"hi\"")].value"#, &value), "");
+    }
+
+    #[test]
+    fn splits_outside_quotes_and_brackets() {
+        assert_eq!(
+            split_unquoted("NAME:.name,EP:.a[?(@.label=='x,y')].value", ','),
+            vec!["NAME:.name", "EP:.a[?(@.label=='x,y')].value"]
+        );
+        assert_eq!(split_unquoted("a", ','), vec!["a"]);
+        assert_eq!(split_unquoted("a,,b", ','), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn filters_by_non_string_literals() {
+        let value = json!([{ "n": 1, "ok": true }, { "n": 2, "ok": false }]);
+
+        assert_eq!(render("[?(@.n==2)].ok", &value), "false");
+        assert_eq!(render("[?(@.ok==true)].n", &value), "1");
+    }
+
+    #[test]
+    fn keeps_the_raw_expression_for_display() {
+        let expr: JsonPath = "{.spec.network}".parse().unwrap();
+
+        assert_eq!(expr.to_string(), "{.spec.network}");
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in [
+            ".annotations[0",
+            ".spec..network",
+            "[?(@.label~='x')]",
+            "[?(label=='x')]",
+            "[?(@.label==unquoted)]",
+            "[-1]",
+        ] {
+            assert!(expr.parse::<JsonPath>().is_err(), "{expr} should fail");
+        }
+    }
+}
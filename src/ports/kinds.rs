@@ -0,0 +1,21 @@
+use clap::Parser;
+
+use super::format::{OutputArgs, OutputFormat};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let metadata = cli.client.find_metadata().await?;
+
+    if metadata.is_empty() && matches!(args.output.format, OutputFormat::Table) {
+        println!("No kinds found");
+        return Ok(());
+    }
+
+    args.output.format.pretty_print_kinds(&metadata);
+    Ok(())
+}
@@ -2,15 +2,27 @@ use clap::Parser;
 
 use crate::{
     context::extract_context_data,
-    rpc,
+    rpc::{is_repeated_page, DEFAULT_PAGE_SIZE, MAX_PAGES},
 };
 
-use super::format::OutputFormat;
+use super::format::{OutputArgs, OutputFormat};
 
 #[derive(Parser)]
 pub struct Args {
-    #[clap(short, long, default_value_t, value_enum)]
-    pub output: OutputFormat,
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    /// only fetch this page of results, starting at 1
+    #[arg(long, conflicts_with = "all")]
+    pub page: Option<u32>,
+
+    /// how many ports to fetch on each request, at most 100
+    #[arg(long, default_value_t = DEFAULT_PAGE_SIZE, value_parser = clap::value_parser!(u32).range(1..=DEFAULT_PAGE_SIZE as i64))]
+    pub page_size: u32,
+
+    /// fetch every page of results (the default unless --page is set)
+    #[arg(long, action)]
+    pub all: bool,
 }
 
 pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
@@ -19,14 +31,68 @@ pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
         .as_ref()
         .ok_or(miette::miette!("can't list ports without a context"))?;
 
-    let (api_key, project_id, _) = extract_context_data(cli).await?;
-    let response = rpc::resources::find(&api_key, &project_id).await?;
+    let (client, project_id, _) = extract_context_data(cli).await?;
+
+    if let Some(page) = args.page {
+        let response = client
+            .find_resources_page(&project_id, page, args.page_size)
+            .await?;
+
+        if response.is_empty() && matches!(args.output.format, OutputFormat::Table) {
+            println!("No ports found");
+            return Ok(());
+        }
+
+        let first = page.saturating_sub(1) as usize * args.page_size as usize + 1;
+        args.output.format.pretty_print_page(response, first);
+        return Ok(());
+    }
+
+    // tables and line oriented formats are printed as pages arrive, the rest
+    // need the whole list to lay out or wrap the output
+    let streaming = matches!(
+        args.output.format,
+        OutputFormat::Table | OutputFormat::JsonPath(_)
+    );
+
+    let mut response = vec![];
+    let mut previous = vec![];
+    let mut found = 0;
 
-    if response.is_empty() {
+    for page in 1..=MAX_PAGES {
+        let batch = client
+            .find_resources_page(&project_id, page, args.page_size)
+            .await?;
+
+        if is_repeated_page(page, &batch, &previous) {
+            break;
+        }
+
+        let exhausted = batch.len() < args.page_size as usize;
+        let first = found + 1;
+        found += batch.len();
+
+        match streaming {
+            true if batch.is_empty() => (),
+            true => args.output.format.pretty_print_page(batch.clone(), first),
+            false => response.extend(batch.iter().cloned()),
+        }
+
+        previous = batch;
+
+        if exhausted {
+            break;
+        }
+    }
+
+    if found == 0 && matches!(args.output.format, OutputFormat::Table) {
         println!("No ports found");
         return Ok(());
     }
 
-    args.output.pretty_print(response);
+    if !streaming {
+        args.output.format.pretty_print(response);
+    }
+
     Ok(())
 }
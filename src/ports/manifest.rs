@@ -0,0 +1,380 @@
+use dmtri::demeter::ops::v1alpha::Resource;
+use miette::{Context as _, IntoDiagnostic};
+use serde::{Deserialize, Serialize};
+use std::io::Read as _;
+
+/// A declarative description of a port, as read by `apply` and friends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub spec: serde_json::Value,
+}
+
+/// Spec fields that are generated by the server and must not be replayed
+/// into a different project.
+const GENERATED_SPEC_FIELDS: &[&str] = &["authToken"];
+
+impl Manifest {
+    /// Builds a portable manifest out of a live resource, dropping its id and
+    /// any server-generated spec field.
+    pub fn from_resource(resource: &Resource) -> miette::Result<Self> {
+        let mut spec = parse_spec(resource)?;
+
+        if let Some(spec) = spec.as_object_mut() {
+            for field in GENERATED_SPEC_FIELDS {
+                spec.remove(*field);
+            }
+        }
+
+        Ok(Self {
+            id: None,
+            kind: resource.kind.clone(),
+            name: Some(resource.name.clone()),
+            spec,
+        })
+    }
+
+    /// The spec a live port should end up with once the manifest is applied.
+    /// The manifest describes the whole spec, so fields it leaves out are
+    /// removed, except for the ones generated by the server.
+    pub fn desired_spec(&self, current: &serde_json::Value) -> serde_json::Value {
+        let mut desired = self.spec.clone();
+
+        if let (Some(desired), Some(current)) = (desired.as_object_mut(), current.as_object()) {
+            for field in GENERATED_SPEC_FIELDS {
+                if let Some(value) = current.get(*field) {
+                    desired.entry(field.to_string()).or_insert(value.clone());
+                }
+            }
+        }
+
+        desired
+    }
+}
+
+fn parse_spec(resource: &Resource) -> miette::Result<serde_json::Value> {
+    serde_json::from_str(&resource.spec)
+        .into_diagnostic()
+        .context(format!("parsing spec of port {}", resource.name))
+}
+
+fn find_explicit<'a>(
+    manifest: &Manifest,
+    live: &'a [Resource],
+) -> miette::Result<Option<&'a Resource>> {
+    let found = match (&manifest.id, &manifest.name) {
+        (Some(id), _) => match live.iter().find(|r| &r.id == id) {
+            Some(x) => Some(x),
+            None => miette::bail!("port with id {id} doesn't exist in the current project"),
+        },
+        (None, Some(name)) => live.iter().find(|r| &r.name == name),
+        (None, None) => None,
+    };
+
+    if let Some(found) = found {
+        if found.kind != manifest.kind {
+            miette::bail!(
+                "port {} is a {}, but the manifest declares a {}",
+                found.name,
+                found.kind,
+                manifest.kind
+            );
+        }
+    }
+
+    Ok(found)
+}
+
+/// Pairs each manifest with the live port it describes, if any. Ports are
+/// matched by id, then by name. Since names are assigned by the server, a
+/// manifest without an id that matches neither is paired with a port of the
+/// same kind that already has its spec, so applying it twice doesn't create a
+/// duplicate. Each port is paired once, so identical manifests take
+/// identical ports one by one.
+pub fn match_live<'a>(
+    manifests: &[Manifest],
+    live: &'a [Resource],
+) -> miette::Result<Vec<Option<&'a Resource>>> {
+    let mut matched: Vec<Option<&Resource>> = vec![];
+
+    for manifest in manifests {
+        let found = find_explicit(manifest, live)?;
+
+        if let Some(found) = found {
+            if matched.iter().flatten().any(|x| x.id == found.id) {
+                miette::bail!("port {} is targeted by more than one manifest", found.name);
+            }
+        }
+
+        matched.push(found);
+    }
+
+    for (i, manifest) in manifests.iter().enumerate() {
+        if matched[i].is_some() || manifest.id.is_some() {
+            continue;
+        }
+
+        let mut found = None;
+
+        for resource in live.iter().filter(|r| r.kind == manifest.kind) {
+            if matched.iter().flatten().any(|x| x.id == resource.id) {
+                continue;
+            }
+
+            let current = parse_spec(resource)?;
+
+            if manifest.desired_spec(&current) == current {
+                found = Some(resource);
+                break;
+            }
+        }
+
+        matched[i] = found;
+    }
+
+    Ok(matched)
+}
+
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+pub enum ManifestFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn serialize(&self, manifests: &[Manifest]) -> miette::Result<String> {
+        match self {
+            ManifestFormat::Yaml => serde_yaml::to_string(manifests).into_diagnostic(),
+            ManifestFormat::Json => serde_json::to_string_pretty(manifests).into_diagnostic(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Document {
+    Many(Vec<Manifest>),
+    One(Manifest),
+}
+
+/// Reads the content of a file, or stdin when the path is `-`.
+pub fn read_input(path: &str) -> miette::Result<String> {
+    if path == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .into_diagnostic()
+            .context("reading from stdin")?;
+
+        return Ok(buffer);
+    }
+
+    std::fs::read_to_string(path)
+        .into_diagnostic()
+        .context(format!("reading file {path}"))
+}
+
+/// Loads manifests from a json or yaml file. Each yaml document can hold a
+/// single manifest or a list of them.
+pub fn load_manifests(path: &str) -> miette::Result<Vec<Manifest>> {
+    let raw = read_input(path)?;
+
+    let mut out = vec![];
+
+    for document in serde_yaml::Deserializer::from_str(&raw) {
+        let document = Document::deserialize(document)
+            .into_diagnostic()
+            .context("parsing manifests")?;
+
+        match document {
+            Document::Many(x) => out.extend(x),
+            Document::One(x) => out.push(x),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn manifest(id: Option<&str>, name: Option<&str>, spec: Value) -> Manifest {
+        Manifest {
+            id: id.map(String::from),
+            kind: "CardanoNodePort".into(),
+            name: name.map(String::from),
+            spec,
+        }
+    }
+
+    fn port(id: &str, kind: &str, spec: Value) -> Resource {
+        Resource {
+            id: id.into(),
+            name: format!("port-{id}"),
+            kind: kind.into(),
+            spec: spec.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn matched_ids(manifests: &[Manifest], live: &[Resource]) -> Vec<Option<String>> {
+        match_live(manifests, live)
+            .unwrap()
+            .into_iter()
+            .map(|x| x.map(|x| x.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_by_id_then_name() {
+        let live = [
+            port("1", "CardanoNodePort", json!({ "network": "mainnet" })),
+            port("2", "CardanoNodePort", json!({ "network": "preprod" })),
+        ];
+
+        let manifests = [
+            manifest(Some("2"), None, json!({ "network": "mainnet" })),
+            manifest(None, Some("port-1"), json!({ "network": "preview" })),
+        ];
+
+        assert_eq!(
+            matched_ids(&manifests, &live),
+            vec![Some("2".into()), Some("1".into())]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_ids_kind_mismatches_and_duplicates() {
+        let live = [port("1", "KupoPort", json!({}))];
+
+        let unknown = [manifest(Some("9"), None, json!({}))];
+        assert!(match_live(&unknown, &live).is_err());
+
+        let mismatch = [manifest(Some("1"), None, json!({}))];
+        assert!(match_live(&mismatch, &live).is_err());
+
+        let live = [port("1", "CardanoNodePort", json!({}))];
+        let duplicate = [
+            manifest(Some("1"), None, json!({})),
+            manifest(None, Some("port-1"), json!({})),
+        ];
+        assert!(match_live(&duplicate, &live).is_err());
+    }
+
+    #[test]
+    fn matches_id_less_manifests_by_spec() {
+        let live = [
+            port("1", "KupoPort", json!({ "network": "mainnet" })),
+            port(
+                "2",
+                "CardanoNodePort",
+                json!({ "network": "mainnet", "authToken": "abc" }),
+            ),
+        ];
+
+        let manifests = [
+            manifest(None, None, json!({ "network": "mainnet" })),
+            manifest(None, None, json!({ "network": "preprod" })),
+        ];
+
+        assert_eq!(matched_ids(&manifests, &live), vec![Some("2".into()), None]);
+    }
+
+    #[test]
+    fn pairs_identical_manifests_with_identical_ports() {
+        let spec = json!({ "network": "mainnet" });
+
+        let live = [
+            port("1", "CardanoNodePort", spec.clone()),
+            port("2", "CardanoNodePort", spec.clone()),
+        ];
+
+        let two = [
+            manifest(None, None, spec.clone()),
+            manifest(None, None, spec.clone()),
+        ];
+        assert_eq!(
+            matched_ids(&two, &live),
+            vec![Some("1".into()), Some("2".into())]
+        );
+
+        let three = [
+            manifest(None, None, spec.clone()),
+            manifest(None, None, spec.clone()),
+            manifest(None, None, spec.clone()),
+        ];
+        assert_eq!(
+            matched_ids(&three, &live),
+            vec![Some("1".into()), Some("2".into()), None]
+        );
+    }
+
+    #[test]
+    fn explicit_matches_are_not_reused_by_spec() {
+        let spec = json!({ "network": "mainnet" });
+        let live = [port("1", "CardanoNodePort", spec.clone())];
+
+        let manifests = [
+            manifest(None, None, spec.clone()),
+            manifest(Some("1"), None, spec.clone()),
+        ];
+
+        assert_eq!(matched_ids(&manifests, &live), vec![None, Some("1".into())]);
+    }
+
+    #[test]
+    fn exported_manifests_drop_generated_fields() {
+        let live = port(
+            "1",
+            "CardanoNodePort",
+            json!({ "network": "mainnet", "authToken": "abc" }),
+        );
+
+        let exported = Manifest::from_resource(&live).unwrap();
+
+        assert_eq!(exported.id, None);
+        assert_eq!(exported.name.as_deref(), Some("port-1"));
+        assert_eq!(exported.kind, "CardanoNodePort");
+        assert_eq!(exported.spec, json!({ "network": "mainnet" }));
+    }
+
+    #[test]
+    fn desired_spec_keeps_generated_fields_and_drops_the_rest() {
+        let current = json!({ "network": "mainnet", "tier": 1, "authToken": "abc" });
+
+        let desired = manifest(None, None, json!({ "network": "preprod" })).desired_spec(&current);
+
+        assert_eq!(desired, json!({ "network": "preprod", "authToken": "abc" }));
+    }
+
+    #[test]
+    fn desired_spec_prefers_the_manifest_values() {
+        let current = json!({ "authToken": "abc" });
+
+        let desired = manifest(None, None, json!({ "authToken": "xyz" })).desired_spec(&current);
+
+        assert_eq!(desired, json!({ "authToken": "xyz" }));
+    }
+
+    #[test]
+    fn applying_an_exported_manifest_changes_nothing() {
+        let live = port(
+            "1",
+            "CardanoNodePort",
+            json!({ "network": "mainnet", "authToken": "abc" }),
+        );
+        let current: Value = serde_json::from_str(&live.spec).unwrap();
+
+        let exported = Manifest::from_resource(&live).unwrap();
+
+        assert_eq!(exported.desired_spec(&current), current);
+    }
+}
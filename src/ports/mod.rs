@@ -2,10 +2,20 @@ use clap::{command, Parser};
 
 pub mod create;
 mod delete;
-mod format;
+mod diff;
+mod edit;
+mod explain;
+mod export;
+pub mod format;
+mod jsonpath;
+mod kinds;
 mod list;
+pub mod manifest;
+pub mod patch;
+pub mod schema;
 mod show;
 mod tunnel;
+mod update;
 
 #[derive(Parser)]
 pub struct Args {
@@ -22,20 +32,62 @@ pub enum Commands {
     Show(show::Args),
     /// Create a new port
     Create(create::Args),
+    /// Update a port spec with a merge patch or single field values
+    Update(update::Args),
+    /// Edit a port spec using your default editor
+    Edit(edit::Args),
     /// Delete a port
     #[command(alias = "rm")]
     Delete(delete::Args),
     /// Create a local tunnel to a remote port
     Tunnel(tunnel::Args),
+    /// Describe the fields accepted by a port kind
+    Explain(explain::Args),
+    /// List the available port kinds and their preset options
+    Kinds(kinds::Args),
+    /// Export ports as manifests that can be applied elsewhere
+    Export(export::Args),
+    /// Show the differences between a manifest and the live ports, exits with 9 if there are any
+    Diff(diff::Args),
     // Disable(list::Args),
 }
 
+impl Args {
+    /// Whether the command was asked to print json, in which case failures
+    /// are reported as json too.
+    pub fn wants_json(&self) -> bool {
+        let output = match &self.command {
+            Commands::List(x) => &x.output.format,
+            Commands::Show(x) => &x.output.format,
+            Commands::Create(x) => &x.output.format,
+            Commands::Update(x) => &x.output.format,
+            Commands::Edit(x) => &x.output.format,
+            Commands::Kinds(x) => &x.output.format,
+            Commands::Export(x) => return matches!(x.format, manifest::ManifestFormat::Json),
+            // listed one by one, so that a new command with an output flag
+            // can't be left out
+            Commands::Delete(_)
+            | Commands::Tunnel(_)
+            | Commands::Explain(_)
+            | Commands::Diff(_) => return false,
+        };
+
+        matches!(output, format::OutputFormat::Json)
+    }
+}
+
 pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
     match args.command {
         Commands::List(x) => list::run(x, cli).await,
         Commands::Show(x) => show::run(x, cli).await,
         Commands::Create(x) => create::run(x, cli).await,
+        Commands::Update(x) => update::run(x, cli).await,
+        Commands::Edit(x) => edit::run(x, cli).await,
         Commands::Delete(x) => delete::run(x, cli).await,
         Commands::Tunnel(x) => tunnel::run(x, cli).await,
+        Commands::Explain(x) => explain::run(x, cli).await,
+        Commands::Kinds(x) => kinds::run(x, cli).await,
+        Commands::Export(x) => export::run(x, cli).await,
+        Commands::Diff(x) => diff::run(x, cli).await,
     }
 }
@@ -0,0 +1,156 @@
+use serde_json::{Map, Value};
+
+/// Applies a JSON merge patch (RFC 7386) on top of the target value.
+pub fn apply(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(x) => x,
+        other => {
+            *target = other.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    let target = target.as_object_mut().unwrap();
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+            continue;
+        }
+
+        apply(target.entry(key.clone()).or_insert(Value::Null), value);
+    }
+}
+
+/// Computes the JSON merge patch that turns `old` into `new`. Removed fields
+/// are set to null, as required by RFC 7386.
+pub fn diff(old: &Value, new: &Value) -> Value {
+    let (old, new) = match (old, new) {
+        (Value::Object(old), Value::Object(new)) => (old, new),
+        (_, new) => return new.clone(),
+    };
+
+    let mut patch = Map::new();
+
+    for key in old.keys() {
+        if !new.contains_key(key) {
+            patch.insert(key.clone(), Value::Null);
+        }
+    }
+
+    for (key, value) in new {
+        match old.get(key) {
+            Some(previous) if previous == value => (),
+            Some(previous) => {
+                patch.insert(key.clone(), diff(previous, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Value::Object(patch)
+}
+
+/// Builds a patch that sets the value at a dotted path, creating the
+/// intermediate objects on the way.
+pub fn set_path(patch: &mut Value, path: &str, value: Value) {
+    let mut current = patch;
+
+    for segment in path.split('.') {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+
+        current = current
+            .as_object_mut()
+            .unwrap()
+            .entry(segment.to_owned())
+            .or_insert(Value::Null);
+    }
+
+    *current = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn apply_merges_objects_and_removes_nulls() {
+        let mut target = json!({ "network": "mainnet", "tier": 1, "extra": { "a": 1, "b": 2 } });
+
+        apply(
+            &mut target,
+            &json!({ "tier": 2, "extra": { "b": null, "c": 3 }, "network": null }),
+        );
+
+        assert_eq!(target, json!({ "tier": 2, "extra": { "a": 1, "c": 3 } }));
+    }
+
+    #[test]
+    fn apply_replaces_arrays_and_non_objects() {
+        let mut target = json!({ "items": [1, 2, 3], "value": "x" });
+
+        apply(
+            &mut target,
+            &json!({ "items": [4], "value": { "nested": true } }),
+        );
+
+        assert_eq!(target, json!({ "items": [4], "value": { "nested": true } }));
+
+        apply(&mut target, &json!("scalar"));
+
+        assert_eq!(target, json!("scalar"));
+    }
+
+    #[test]
+    fn diff_nulls_removed_fields() {
+        let old = json!({ "network": "mainnet", "tier": 1, "extra": { "a": 1, "b": 2 } });
+        let new = json!({ "network": "mainnet", "extra": { "a": 1 } });
+
+        assert_eq!(
+            diff(&old, &new),
+            json!({ "tier": null, "extra": { "b": null } })
+        );
+    }
+
+    #[test]
+    fn diff_of_equal_values_is_empty() {
+        let value = json!({ "network": "mainnet", "items": [1, 2] });
+
+        assert_eq!(diff(&value, &value), json!({}));
+    }
+
+    #[test]
+    fn applying_the_diff_gives_the_new_value() {
+        let old = json!({ "a": 1, "b": { "c": [1, 2], "d": "x" }, "e": true });
+        let new = json!({ "a": 2, "b": { "c": [3] }, "f": { "g": null } });
+
+        let mut patched = old.clone();
+        apply(&mut patched, &diff(&old, &new));
+
+        // nulls can't be set through a merge patch, they remove the field
+        assert_eq!(patched, json!({ "a": 2, "b": { "c": [3] }, "f": {} }));
+    }
+
+    #[test]
+    fn set_path_creates_intermediate_objects() {
+        let mut patch = json!({ "network": "mainnet" });
+
+        set_path(&mut patch, "resources.limits.cpu", json!("500m"));
+        set_path(&mut patch, "network", json!("preprod"));
+
+        assert_eq!(
+            patch,
+            json!({ "network": "preprod", "resources": { "limits": { "cpu": "500m" } } })
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+    CustomResourceDefinition, JSONSchemaProps, JSONSchemaPropsOrArray, JSONSchemaPropsOrBool,
+};
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceSpan};
+use serde_json::Value;
+
+/// Returns the openAPIV3Schema of the CRD storage version, falling back to
+/// the first version that declares a schema.
+pub fn root_schema(crd: &CustomResourceDefinition) -> Option<&JSONSchemaProps> {
+    let versions = &crd.spec.versions;
+
+    versions
+        .iter()
+        .find(|v| v.storage)
+        .and_then(|v| v.schema.as_ref())
+        .or_else(|| versions.iter().find_map(|v| v.schema.as_ref()))
+        .and_then(|s| s.open_api_v3_schema.as_ref())
+}
+
+/// Returns the schema that applies to the `spec` field of the resource.
+pub fn spec_schema(crd: &CustomResourceDefinition) -> Option<&JSONSchemaProps> {
+    root_schema(crd)?.properties.as_ref()?.get("spec")
+}
+
+/// Returns the schema of a field given its dotted path within the spec.
+pub fn field_schema<'a>(
+    spec_schema: &'a JSONSchemaProps,
+    path: &str,
+) -> Option<&'a JSONSchemaProps> {
+    path.split('.').try_fold(spec_schema, |current, segment| {
+        current.properties.as_ref()?.get(segment)
+    })
+}
+
+#[derive(Debug)]
+pub struct FieldError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid spec for {kind}, {} field(s) failed validation", errors.len())]
+pub struct InvalidSpec {
+    pub kind: String,
+    pub errors: Vec<FieldError>,
+    src: NamedSource,
+    spans: HashMap<String, SourceSpan>,
+}
+
+impl Diagnostic for InvalidSpec {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new("dmtrctl::invalid_spec"))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "run `dmtrctl ports explain {}` to see the accepted fields",
+            self.kind
+        )))
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.src)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labels = self.errors.iter().map(|e| {
+            let span = self
+                .spans
+                .get(&e.path)
+                .cloned()
+                .unwrap_or(SourceSpan::from((0, 1)));
+
+            LabeledSpan::new_with_span(Some(format!("{}: {}", e.path, e.message)), span)
+        });
+
+        Some(Box::new(labels))
+    }
+}
+
+/// Validates a resource spec against the CRD schema of its kind. Types, enums
+/// and required fields are checked; unknown fields are left for the server to
+/// prune.
+pub fn validate(crd: &CustomResourceDefinition, spec: &Value) -> miette::Result<()> {
+    let schema = match spec_schema(crd) {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    let mut errors = vec![];
+    walk(schema, spec, "", &mut errors);
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let mut rendered = String::new();
+    let mut spans = HashMap::new();
+    render(spec, "", 0, &mut rendered, &mut spans);
+
+    let kind = crd.spec.names.kind.clone();
+
+    Err(InvalidSpec {
+        src: NamedSource::new(format!("{kind} spec"), rendered),
+        kind,
+        errors,
+        spans,
+    }
+    .into())
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{parent}.{key}")
+}
+
+fn item_path(parent: &str, index: usize) -> String {
+    format!("{parent}[{index}]")
+}
+
+fn display_path(path: &str) -> String {
+    match path {
+        "" => ".".into(),
+        x => x.into(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(x) if x.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        _ => true,
+    }
+}
+
+fn walk(schema: &JSONSchemaProps, value: &Value, path: &str, errors: &mut Vec<FieldError>) {
+    let mut fail = |message: String| {
+        errors.push(FieldError {
+            path: display_path(path),
+            message,
+        })
+    };
+
+    if value.is_null() {
+        if !schema.nullable.unwrap_or_default() {
+            fail("must not be null".into());
+        }
+        return;
+    }
+
+    if schema.x_kubernetes_int_or_string.unwrap_or_default() {
+        if !matches_type("integer", value) && !value.is_string() {
+            fail(format!(
+                "expected integer or string, found {}",
+                type_name(value)
+            ));
+        }
+        return;
+    }
+
+    if let Some(expected) = &schema.type_ {
+        if !matches_type(expected, value) {
+            fail(format!("expected {}, found {}", expected, type_name(value)));
+            return;
+        }
+    }
+
+    if let Some(allowed) = &schema.enum_ {
+        if !allowed.iter().any(|x| x.0 == *value) {
+            let options = allowed
+                .iter()
+                .map(|x| x.0.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            fail(format!(
+                "unsupported value {value}, expected one of: {options}"
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            for required in schema.required.iter().flatten() {
+                if !map.contains_key(required) {
+                    fail(format!("missing required field {required}"));
+                }
+            }
+
+            for (key, child) in map {
+                let child_schema = schema.properties.as_ref().and_then(|p| p.get(key)).or(
+                    match &schema.additional_properties {
+                        Some(JSONSchemaPropsOrBool::Schema(x)) => Some(x.as_ref()),
+                        _ => None,
+                    },
+                );
+
+                if let Some(child_schema) = child_schema {
+                    walk(child_schema, child, &child_path(path, key), errors);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let item_schema = match &schema.items {
+                    Some(JSONSchemaPropsOrArray::Schema(x)) => Some(x.as_ref()),
+                    Some(JSONSchemaPropsOrArray::Schemas(x)) => x.get(index),
+                    None => None,
+                };
+
+                if let Some(item_schema) = item_schema {
+                    walk(item_schema, item, &item_path(path, index), errors);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Renders a value the same way `serde_json::to_string_pretty` does, while
+/// keeping track of the byte span that each path occupies in the output.
+fn render(
+    value: &Value,
+    path: &str,
+    indent: usize,
+    out: &mut String,
+    spans: &mut HashMap<String, SourceSpan>,
+) {
+    let start = out.len();
+    let pad = "  ".repeat(indent + 1);
+
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            for (i, (key, child)) in map.iter().enumerate() {
+                let child_path = child_path(path, key);
+                let key_start = out.len() + pad.len();
+
+                out.push_str(&pad);
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push_str(": ");
+                render(child, &child_path, indent + 1, out, spans);
+
+                // highlight the whole member, key included
+                let key_span = SourceSpan::from(key_start..out.len());
+                spans.insert(display_path(&child_path), key_span);
+
+                if i + 1 < map.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&pad);
+                render(item, &item_path(path, i), indent + 1, out, spans);
+
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+
+    spans
+        .entry(display_path(path))
+        .or_insert(SourceSpan::from(start..out.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn crd() -> CustomResourceDefinition {
+        let spec = json!({
+            "type": "object",
+            "required": ["network"],
+            "properties": {
+                "network": { "type": "string", "enum": ["mainnet", "preprod"] },
+                "throughputTier": { "x-kubernetes-int-or-string": true },
+                "replicas": { "type": "integer" },
+                "authToken": { "type": "string", "nullable": true },
+                "labels": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                },
+                "ports": { "type": "array", "items": { "type": "integer" } },
+            },
+        });
+
+        serde_json::from_value(json!({
+            "apiVersion": "apiextensions.k8s.io/v1",
+            "kind": "CustomResourceDefinition",
+            "metadata": { "name": "cardanonodeports.demeter.run" },
+            "spec": {
+                "group": "demeter.run",
+                "scope": "Namespaced",
+                "names": {
+                    "kind": "CardanoNodePort",
+                    "plural": "cardanonodeports",
+                },
+                "versions": [
+                    {
+                        "name": "v1alpha0",
+                        "served": true,
+                        "storage": false,
+                        "schema": { "openAPIV3Schema": { "type": "object" } },
+                    },
+                    {
+                        "name": "v1alpha1",
+                        "served": true,
+                        "storage": true,
+                        "schema": {
+                            "openAPIV3Schema": {
+                                "type": "object",
+                                "properties": { "spec": spec },
+                            },
+                        },
+                    },
+                ],
+            },
+        }))
+        .unwrap()
+    }
+
+    fn failures(spec: Value) -> Vec<(String, String)> {
+        let report = validate(&crd(), &spec).unwrap_err();
+        let invalid = report.downcast_ref::<InvalidSpec>().unwrap();
+
+        invalid
+            .errors
+            .iter()
+            .map(|x| (x.path.clone(), x.message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn uses_the_storage_version() {
+        let crd = crd();
+        let schema = spec_schema(&crd).unwrap();
+
+        assert!(schema.properties.as_ref().unwrap().contains_key("network"));
+    }
+
+    #[test]
+    fn finds_nested_fields() {
+        let crd = crd();
+        let schema = spec_schema(&crd).unwrap();
+
+        assert_eq!(
+            field_schema(schema, "labels").unwrap().type_.as_deref(),
+            Some("object")
+        );
+        assert!(field_schema(schema, "network.missing").is_none());
+    }
+
+    #[test]
+    fn accepts_a_valid_spec() {
+        let spec = json!({
+            "network": "mainnet",
+            "throughputTier": "1",
+            "replicas": 2,
+            "authToken": null,
+            "labels": { "team": "infra" },
+            "ports": [3000, 3001],
+            "unknown": "left for the server",
+        });
+
+        assert!(validate(&crd(), &spec).is_ok());
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let spec = json!({
+            "network": "testnet",
+            "throughputTier": 1.5,
+            "replicas": "two",
+            "labels": { "team": 1 },
+            "ports": [3000, "x"],
+        });
+
+        assert_eq!(
+            failures(spec),
+            vec![
+                (
+                    ".labels.team".into(),
+                    "expected string, found integer".into()
+                ),
+                (
+                    ".network".into(),
+                    r#"unsupported value "testnet", expected one of: "mainnet", "preprod""#.into()
+                ),
+                (".ports[1]".into(), "expected integer, found string".into()),
+                (".replicas".into(), "expected integer, found string".into()),
+                (
+                    ".throughputTier".into(),
+                    "expected integer or string, found number".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_and_null_fields() {
+        assert_eq!(
+            failures(json!({ "replicas": null })),
+            vec![
+                (".".into(), "missing required field network".into()),
+                (".replicas".into(), "must not be null".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_like_serde_and_tracks_spans() {
+        let spec = json!({ "network": "mainnet", "ports": [3000], "labels": {} });
+
+        let mut rendered = String::new();
+        let mut spans = HashMap::new();
+        render(&spec, "", 0, &mut rendered, &mut spans);
+
+        assert_eq!(rendered, serde_json::to_string_pretty(&spec).unwrap());
+
+        let text = |path: &str| {
+            let span = spans[path];
+            &rendered[span.offset()..span.offset() + span.len()]
+        };
+
+        assert_eq!(text(".network"), r#""network": "mainnet""#);
+        assert_eq!(text(".ports[0]"), "3000");
+        assert_eq!(text(".labels"), r#""labels": {}"#);
+    }
+}
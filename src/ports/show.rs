@@ -1,16 +1,16 @@
 use clap::Parser;
 
-use crate::{context::extract_context_data, rpc};
+use crate::{context::extract_context_data, errors::Error};
 
-use super::format::OutputFormat;
+use super::format::OutputArgs;
 
 #[derive(Parser)]
 pub struct Args {
     /// the resource uuid
     id: String,
 
-    #[clap(short, long, default_value_t, value_enum)]
-    pub output: OutputFormat,
+    #[command(flatten)]
+    pub output: OutputArgs,
 }
 
 pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
@@ -19,15 +19,14 @@ pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
         .as_ref()
         .ok_or(miette::miette!("can't list ports without a context"))?;
 
-    let (api_key, project_id, _) = extract_context_data(cli).await?;
-    let resouces = rpc::resources::find_by_id(&api_key, &project_id, &args.id).await?;
+    let (client, _, _) = extract_context_data(cli).await?;
+    let response = client
+        .find_resources_by_id(&args.id)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(Error::NotFound(format!("port {} not found", args.id)))?;
 
-    if resouces.is_empty() {
-        println!("No ports found");
-        return Ok(());
-    }
-
-    let response = resouces.first().unwrap();
-    args.output.pretty_print_single(response);
+    args.output.format.pretty_print_single(&response);
     Ok(())
 }
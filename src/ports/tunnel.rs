@@ -1,4 +1,4 @@
-use crate::{context::extract_context_data, rpc};
+use crate::{context::extract_context_data, errors::Error};
 use clap::Parser;
 use colored::Colorize;
 use dmtri::demeter::ops::v1alpha::Resource;
@@ -168,9 +168,9 @@ impl std::fmt::Display for NodeOption {
 }
 
 async fn define_port(port_name: Option<String>, cli: &crate::Cli) -> miette::Result<Resource> {
-    let (api_key, project_id, _) = extract_context_data(cli).await?;
+    let (client, project_id, _) = extract_context_data(cli).await?;
 
-    let response = rpc::resources::find(&api_key, &project_id).await?;
+    let response = client.find_resources(&project_id).await?;
     if response.is_empty() {
         bail!("you don't have any cardano-node ports, run dmtrctl ports create");
     }
@@ -189,14 +189,14 @@ async fn define_port(port_name: Option<String>, cli: &crate::Cli) -> miette::Res
         let port = available
             .into_iter()
             .find(|p| p.0.name == port_name)
-            .ok_or(miette::miette!("can't find port"))?;
+            .ok_or(Error::NotFound(format!("port {port_name} not found")))?;
 
         return Ok(port.0);
     }
 
     let selection = inquire::Select::new("select port", available)
         .prompt()
-        .into_diagnostic()
+        .map_err(Error::from)
         .context("selecting available port")?;
 
     Ok(selection.0)
@@ -235,6 +235,8 @@ impl ClientCounter {
 
 const CARDANO_NODE_KIND: &str = "CardanoNodePort";
 
+const DEFAULT_TUNNEL_DOMAIN: &str = "cnode-m1.demeter.run";
+
 pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
     let ctx = cli
         .context
@@ -248,7 +250,11 @@ pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
         .context("error parsing resource spec")?;
 
     let auth_token = spec.get("authToken").unwrap().as_str().unwrap();
-    let hostname = format!("{}.cnode-m1.demeter.run", auth_token);
+    let domain = ctx
+        .tunnel_domain
+        .as_deref()
+        .unwrap_or(DEFAULT_TUNNEL_DOMAIN);
+    let hostname = format!("{}.{}", auth_token, domain);
 
     let socket_path = define_socket_path(args.socket, &resource.name, &cli.dirs, ctx)
         .context("error defining unix socket path")?;
@@ -0,0 +1,117 @@
+use clap::Parser;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::JSONSchemaProps;
+use miette::{bail, Context as _, IntoDiagnostic};
+
+use crate::{context::extract_context_data, errors::Error};
+
+use super::{
+    create::load_spec,
+    format::{OutputArgs, OutputFormat},
+    patch, schema,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// the resource uuid
+    id: String,
+
+    /// inline json merge patch to apply to the spec
+    #[arg(long, conflicts_with_all = ["patch_file", "set"])]
+    patch: Option<String>,
+
+    /// path to a json or yaml merge patch file, use - for stdin
+    #[arg(long, conflicts_with = "set")]
+    patch_file: Option<String>,
+
+    /// set a spec field using a dotted path, can be repeated (eg: --set
+    /// throughputTier=1)
+    #[arg(long, value_name = "KEY=VALUE")]
+    set: Vec<String>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+fn parse_set_value(
+    path: &str,
+    raw: &str,
+    spec_schema: Option<&JSONSchemaProps>,
+) -> serde_json::Value {
+    let expected = spec_schema
+        .and_then(|s| schema::field_schema(s, path))
+        .and_then(|s| s.type_.as_deref());
+
+    if expected == Some("string") {
+        return serde_json::Value::from(raw);
+    }
+
+    serde_json::from_str(raw).unwrap_or(serde_json::Value::from(raw))
+}
+
+fn build_set_patch(
+    pairs: &[String],
+    spec_schema: Option<&JSONSchemaProps>,
+) -> miette::Result<serde_json::Value> {
+    let mut out = serde_json::Value::Object(Default::default());
+
+    for pair in pairs {
+        let (path, raw) = match pair.split_once('=') {
+            Some(x) => x,
+            None => bail!("invalid --set value {pair}, expected KEY=VALUE"),
+        };
+
+        let value = parse_set_value(path, raw, spec_schema);
+        patch::set_path(&mut out, path, value);
+    }
+
+    Ok(out)
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (client, _, _) = extract_context_data(cli).await?;
+
+    let resource = client
+        .find_resources_by_id(&args.id)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(Error::NotFound(format!("port {} not found", args.id)))?;
+
+    let metadata = client.find_metadata().await?;
+    let crd = metadata
+        .iter()
+        .find(|m| m.crd.spec.names.kind == resource.kind)
+        .map(|m| &m.crd);
+
+    let spec_patch = match (&args.patch, &args.patch_file) {
+        (Some(inline), _) => serde_json::from_str(inline)
+            .into_diagnostic()
+            .context("parsing inline patch")?,
+        (None, Some(path)) => load_spec(path)?,
+        (None, None) if !args.set.is_empty() => {
+            build_set_patch(&args.set, crd.and_then(schema::spec_schema))?
+        }
+        (None, None) => bail!("nothing to update, use --patch, --patch-file or --set"),
+    };
+
+    let mut spec: serde_json::Value = serde_json::from_str(&resource.spec)
+        .into_diagnostic()
+        .context("parsing current resource spec")?;
+
+    patch::apply(&mut spec, &spec_patch);
+
+    if let Some(crd) = crd {
+        schema::validate(crd, &spec)?;
+    }
+
+    let result = client
+        .update_resource(&args.id, &spec_patch.to_string())
+        .await?;
+
+    match args.output.format {
+        OutputFormat::Table => println!("Port {}({}) updated", result.kind, result.id),
+        _ => args.output.format.pretty_print_single(&result),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use clap::Parser;
+
+use crate::{
+    context::{add_context, load_config},
+    init::{apikey::define_api_key, login_client, project_context},
+    ports::format::{OutputArgs, OutputFormat},
+};
+
+use super::format::print_project;
+
+#[derive(Parser)]
+pub struct Args {
+    /// human readable name to identify the project
+    name: String,
+
+    /// also create an api key and save a context for the new project, named
+    /// after its namespace unless a name is given
+    #[arg(long, value_name = "CONTEXT", num_args = 0..=1)]
+    bind: Option<Option<String>>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    // fail before creating anything rather than replacing a saved context
+    if let Some(Some(name)) = &args.bind {
+        if load_config(&cli.dirs)?.contexts.contains_key(name) {
+            miette::bail!("context {name} already exists, pick another name with --bind=<name>");
+        }
+    }
+
+    let client = login_client(cli).await?;
+
+    let project = client.create_project(&args.name).await?;
+
+    match args.output.format {
+        OutputFormat::Table => println!("Project {} ({}) created", project.name, project.namespace),
+        _ => {
+            let created = client.find_project_by_namespace(&project.namespace).await?;
+            print_project(&args.output.format, &created);
+        }
+    }
+
+    if let Some(name) = args.bind {
+        let name = name.unwrap_or(project.namespace.clone());
+
+        let (api_key, key_id) = define_api_key(&client, &project.id).await?;
+        let ctx = project_context(cli, &project, &api_key, key_id.clone());
+
+        if let Err(err) = add_context(&name, ctx, &cli.dirs) {
+            // the key is only reachable through the context, don't leave it orphaned
+            match key_id {
+                Some(id) if client.delete_project_secret(&id).await.is_ok() => {
+                    eprintln!("Revoked the api key created for context {name}");
+                }
+                Some(_) => {
+                    eprintln!("The context wasn't saved and its api key couldn't be revoked, keep it or revoke it from the web console: {api_key}");
+                }
+                None => {}
+            }
+
+            return Err(err);
+        }
+
+        eprintln!("Saved context {name}, run `dmtrctl context use {name}` to make it the default");
+    }
+
+    Ok(())
+}
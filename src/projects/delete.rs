@@ -0,0 +1,48 @@
+use clap::Parser;
+
+use crate::{context::load_config, errors::Error, init::login_client};
+
+#[derive(Parser)]
+pub struct Args {
+    /// namespace of the project
+    #[arg(value_name = "NAMESPACE")]
+    project: String,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let msg = format!(
+        "You are about to delete the project {} along with all its ports. This action cannot be undone. Do you want to proceed?",
+        args.project
+    );
+
+    let confirm = inquire::Confirm::new(&msg).prompt().map_err(Error::from)?;
+
+    if !confirm {
+        return Err(Error::Aborted.into());
+    }
+
+    let client = login_client(cli).await?;
+
+    let project = client.find_project_by_namespace(&args.project).await?;
+
+    client.delete_project(&project.id).await?;
+
+    println!("Project {} deleted", args.project);
+
+    let config = load_config(&cli.dirs)?;
+
+    let mut stale: Vec<&String> = config
+        .contexts
+        .iter()
+        .filter(|(_, ctx)| ctx.namespace.name == args.project)
+        .map(|(name, _)| name)
+        .collect();
+
+    stale.sort();
+
+    for name in stale {
+        println!("Context {name} pointed to it, remove it with `dmtrctl context delete {name}`");
+    }
+
+    Ok(())
+}
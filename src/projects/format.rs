@@ -0,0 +1,79 @@
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{ContentArrangement, Table};
+use dmtri::demeter::ops::v1alpha::Project;
+use serde::Serialize;
+
+use crate::ports::format::{
+    pretty_print_json, pretty_print_yaml, print_custom_columns, print_jsonpath, OutputFormat,
+};
+
+/// The shape in which a project is presented by every output format.
+#[derive(Serialize)]
+pub struct ProjectView {
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&Project> for ProjectView {
+    fn from(project: &Project) -> Self {
+        Self {
+            id: project.id.clone(),
+            name: project.name.clone(),
+            namespace: project.namespace.clone(),
+            status: project.status.clone(),
+            created_at: project.created_at.clone(),
+            updated_at: project.updated_at.clone(),
+        }
+    }
+}
+
+pub fn print_projects(output: &OutputFormat, projects: &[Project]) {
+    let views: Vec<ProjectView> = projects.iter().map(ProjectView::from).collect();
+
+    match output {
+        OutputFormat::Table => pretty_print_project_table(&views),
+        OutputFormat::Json => pretty_print_json(&views),
+        OutputFormat::Yaml => pretty_print_yaml(&views),
+        OutputFormat::JsonPath(expr) => print_jsonpath(expr, &views),
+        OutputFormat::CustomColumns(columns) => print_custom_columns(columns, &views),
+    }
+}
+
+pub fn print_project(output: &OutputFormat, project: &Project) {
+    let view = ProjectView::from(project);
+
+    match output {
+        OutputFormat::Table => pretty_print_project_table(std::slice::from_ref(&view)),
+        OutputFormat::Json => pretty_print_json(&view),
+        OutputFormat::Yaml => pretty_print_yaml(&view),
+        OutputFormat::JsonPath(expr) => print_jsonpath(expr, &[view]),
+        OutputFormat::CustomColumns(columns) => print_custom_columns(columns, &[view]),
+    }
+}
+
+fn pretty_print_project_table(projects: &[ProjectView]) {
+    let mut table = Table::new();
+
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["ID", "Namespace", "Name", "Status", "Created At"]);
+
+    for project in projects {
+        table.add_row(vec![
+            &project.id,
+            &project.namespace,
+            &project.name,
+            &project.status,
+            &project.created_at,
+        ]);
+    }
+
+    println!("{table}");
+}
@@ -0,0 +1,28 @@
+use clap::Parser;
+
+use crate::{
+    init::login_client,
+    ports::format::{OutputArgs, OutputFormat},
+};
+
+use super::format::print_projects;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let client = login_client(cli).await?;
+
+    let projects = client.find_projects().await?;
+
+    if projects.is_empty() && matches!(args.output.format, OutputFormat::Table) {
+        println!("No projects found, run `dmtrctl projects create <name>` to create one");
+        return Ok(());
+    }
+
+    print_projects(&args.output.format, &projects);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use clap::Parser;
+
+mod create;
+mod delete;
+mod format;
+mod list;
+mod rename;
+mod show;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Parser)]
+pub enum Commands {
+    /// List the projects you have access to
+    #[command(alias = "ls")]
+    List(list::Args),
+    /// Show the details of a project
+    Show(show::Args),
+    /// Create a new project
+    Create(create::Args),
+    /// Change the name of a project
+    Rename(rename::Args),
+    /// Delete a project
+    #[command(alias = "rm")]
+    Delete(delete::Args),
+}
+
+impl Args {
+    /// Whether the command was asked to print json, in which case failures
+    /// are reported as json too.
+    pub fn wants_json(&self) -> bool {
+        let output = match &self.command {
+            Commands::List(x) => &x.output.format,
+            Commands::Show(x) => &x.output.format,
+            Commands::Create(x) => &x.output.format,
+            Commands::Rename(x) => &x.output.format,
+            Commands::Delete(_) => return false,
+        };
+
+        matches!(output, crate::ports::format::OutputFormat::Json)
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    match args.command {
+        Commands::List(x) => list::run(x, cli).await,
+        Commands::Show(x) => show::run(x, cli).await,
+        Commands::Create(x) => create::run(x, cli).await,
+        Commands::Rename(x) => rename::run(x, cli).await,
+        Commands::Delete(x) => delete::run(x, cli).await,
+    }
+}
+
+/// The namespace given in the command line, or the one of the current
+/// context.
+fn namespace_or_current(namespace: Option<String>, cli: &crate::Cli) -> miette::Result<String> {
+    namespace
+        .or(cli.context.as_ref().map(|x| x.namespace.name.clone()))
+        .ok_or(miette::miette!(
+            "missing project namespace, and there's no context in use"
+        ))
+}
@@ -0,0 +1,39 @@
+use clap::Parser;
+
+use crate::{
+    init::login_client,
+    ports::format::{OutputArgs, OutputFormat},
+};
+
+use super::format::print_project;
+
+#[derive(Parser)]
+pub struct Args {
+    /// namespace of the project
+    #[arg(value_name = "NAMESPACE")]
+    project: String,
+
+    /// the new name for the project
+    name: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let client = login_client(cli).await?;
+
+    let project = client.find_project_by_namespace(&args.project).await?;
+
+    let updated = client.update_project(&project.id, &args.name).await?;
+
+    match args.output.format {
+        OutputFormat::Table => println!(
+            "Project {} renamed from {} to {}",
+            updated.namespace, project.name, updated.name
+        ),
+        _ => print_project(&args.output.format, &updated),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use clap::Parser;
+
+use crate::{init::login_client, ports::format::OutputArgs};
+
+use super::{format::print_project, namespace_or_current};
+
+#[derive(Parser)]
+pub struct Args {
+    /// namespace of the project, defaults to the one of the current context
+    #[arg(value_name = "NAMESPACE")]
+    project: Option<String>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let namespace = namespace_or_current(args.project, cli)?;
+
+    let client = login_client(cli).await?;
+
+    let project = client.find_project_by_namespace(&namespace).await?;
+
+    print_project(&args.output.format, &project);
+    Ok(())
+}
@@ -1,34 +1,46 @@
 use tonic::metadata::MetadataValue;
 use tonic::service::Interceptor;
-use tonic::Request;
+use tonic::{Request, Status};
 
 pub type Secret = String;
 pub type ProjectId = String;
 
+#[derive(Clone)]
 pub enum Credential {
     Auth0(String),
     Secret((ProjectId, Secret)),
 }
 
-pub async fn interceptor(credential: Credential) -> impl Interceptor {
-    move |mut req: Request<()>| {
-        match &credential {
-            Credential::Auth0(token) => {
-                req.metadata_mut().insert(
-                    "authorization",
-                    MetadataValue::try_from(&format!("Bearer {}", token)).unwrap(),
-                );
-            }
-            Credential::Secret((project_namespace, secret)) => {
-                req.metadata_mut().insert(
-                    "project-namespace",
-                    MetadataValue::try_from(project_namespace).unwrap(),
-                );
-                req.metadata_mut()
-                    .insert("dmtr-api-key", MetadataValue::try_from(secret).unwrap());
-            }
+/// Adds the credential headers, if any, to every outgoing request.
+#[derive(Clone)]
+pub struct CredentialInterceptor {
+    credential: Option<Credential>,
+}
+
+impl CredentialInterceptor {
+    pub fn new(credential: Option<Credential>) -> Self {
+        Self { credential }
+    }
+}
+
+impl Interceptor for CredentialInterceptor {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        let headers = match &self.credential {
+            Some(Credential::Auth0(token)) => vec![("authorization", format!("Bearer {}", token))],
+            Some(Credential::Secret((project_namespace, secret))) => vec![
+                ("project-namespace", project_namespace.clone()),
+                ("dmtr-api-key", secret.clone()),
+            ],
+            None => vec![],
         };
 
+        for (key, value) in headers {
+            let value = MetadataValue::try_from(value)
+                .map_err(|_| Status::unauthenticated("credential contains invalid characters"))?;
+
+            req.metadata_mut().insert(key, value);
+        }
+
         Ok(req)
     }
 }
@@ -2,34 +2,39 @@ use dmtri::demeter::ops::v1alpha as proto;
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use miette::IntoDiagnostic;
 use serde::Deserialize;
-use tonic::transport::Channel;
 
-use super::get_base_url;
+use crate::errors::RpcError;
 
-pub async fn find() -> miette::Result<Vec<ResourceMetadata>> {
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
+use super::DemeterClient;
 
-    let mut client = proto::metadata_service_client::MetadataServiceClient::new(channel);
+impl DemeterClient {
+    pub async fn find_metadata(&self) -> miette::Result<Vec<ResourceMetadata>> {
+        let response = self
+            .with_retries("fetch_metadata", || {
+                let mut client = proto::metadata_service_client::MetadataServiceClient::new(
+                    self.authenticated_channel(),
+                );
 
-    let request = tonic::Request::new(proto::FetchMetadataRequest::default());
-    let response = client.fetch_metadata(request).await.into_diagnostic()?;
-    let records = response.into_inner().records;
-
-    let metadata: Vec<ResourceMetadata> = records
-        .iter()
-        .map(|m| {
-            Ok(ResourceMetadata {
-                options: serde_json::from_str(&m.options).into_diagnostic()?,
-                crd: serde_json::from_str(&m.crd).into_diagnostic()?,
+                let request = tonic::Request::new(proto::FetchMetadataRequest::default());
+                async move { client.fetch_metadata(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        let records = response.into_inner().records;
+
+        let metadata: Vec<ResourceMetadata> = records
+            .iter()
+            .map(|m| {
+                Ok(ResourceMetadata {
+                    options: serde_json::from_str(&m.options).into_diagnostic()?,
+                    crd: serde_json::from_str(&m.crd).into_diagnostic()?,
+                })
             })
-        })
-        .collect::<miette::Result<Vec<_>>>()?;
+            .collect::<miette::Result<Vec<_>>>()?;
 
-    Ok(metadata)
+        Ok(metadata)
+    }
 }
 
 #[derive(Debug, Deserialize)]
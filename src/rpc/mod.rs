@@ -1,11 +1,315 @@
-use std::env;
+use miette::{Context as _, IntoDiagnostic};
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use std::{collections::hash_map::RandomState, env};
+use tonic::{service::interceptor::InterceptedService, transport::Channel, Code, Status};
+use tracing::warn;
 
 pub mod auth;
 pub mod metadata;
 pub mod projects;
 pub mod resources;
 
-pub fn get_base_url() -> String {
-    let api_base_url = "https://rpc.demeter.run".into();
-    env::var("RPC_BASE_URL").unwrap_or(api_base_url)
+pub const DEFAULT_RPC_URL: &str = "https://rpc.demeter.run";
+
+/// Picks the API endpoint: the url of the active context, then the
+/// `RPC_BASE_URL` env var, then the public endpoint. Switching to a context
+/// must switch clusters, so the env var only applies to contexts without
+/// their own url.
+pub fn get_base_url(context_url: Option<&str>) -> String {
+    let env_url = env::var("RPC_BASE_URL").ok();
+
+    if let (Some(context_url), Some(env_url)) = (context_url, env_url.as_deref()) {
+        if context_url != env_url {
+            eprintln!("warning: ignoring RPC_BASE_URL={env_url}, the context uses {context_url}");
+        }
+    }
+
+    context_url
+        .map(String::from)
+        .or(env_url)
+        .unwrap_or(DEFAULT_RPC_URL.into())
+}
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_RETRIES: u32 = 3;
+
+/// Page size used when listing collections, which is also the largest page
+/// the API hands out.
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Most pages a listing goes through, in case the server keeps handing out
+/// full ones.
+pub const MAX_PAGES: u32 = 1000;
+
+const BACKOFF_BASE: Duration = Duration::from_millis(250);
+const BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// Connection settings applied to the shared channel.
+#[derive(Debug, Clone)]
+pub struct RpcOptions {
+    /// Applies both to establishing the connection and to each request
+    pub timeout: Duration,
+
+    /// How many times idempotent calls are retried on transient errors
+    pub retries: u32,
+}
+
+impl Default for RpcOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            retries: DEFAULT_RETRIES,
+        }
+    }
+}
+
+/// Quota errors aren't retried, they won't clear up within the backoff.
+fn is_transient(status: &Status) -> bool {
+    status.code() == Code::Unavailable || crate::errors::is_timeout(status)
+}
+
+/// Exponential backoff with full jitter: a random delay between zero and
+/// `BACKOFF_BASE * 2^attempt`, capped at `BACKOFF_MAX`.
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX);
+
+    // a freshly seeded hasher is random enough for jitter
+    let random = RandomState::new().build_hasher().finish();
+
+    ceiling.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+/// Whether a full page is the same as the previous one, which means the
+/// server ignores paging and would hand it out forever.
+pub fn is_repeated_page<T: PartialEq>(page: u32, batch: &[T], previous: &[T]) -> bool {
+    let repeated = !batch.is_empty() && batch == previous;
+
+    if repeated {
+        warn!(page, "the api returned the same page twice, stopping");
+    }
+
+    repeated
+}
+
+/// Fetches pages one after the other until the API returns one that isn't
+/// full, which means there are no more records. Stops early on a repeated
+/// page or after `MAX_PAGES`.
+async fn fetch_all_pages<T, F, Fut>(mut fetch_page: F) -> miette::Result<Vec<T>>
+where
+    T: PartialEq + Clone,
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = miette::Result<Vec<T>>>,
+{
+    let mut records = vec![];
+    let mut previous = vec![];
+
+    for page in 1..=MAX_PAGES {
+        let batch = fetch_page(page).await?;
+
+        if is_repeated_page(page, &batch, &previous) {
+            break;
+        }
+
+        let exhausted = batch.len() < DEFAULT_PAGE_SIZE as usize;
+
+        records.extend(batch.iter().cloned());
+        previous = batch;
+
+        if exhausted {
+            break;
+        }
+    }
+
+    Ok(records)
+}
+
+type AuthenticatedChannel = InterceptedService<Channel, auth::CredentialInterceptor>;
+
+/// Typed client for the Demeter RPC API. It owns a single lazily connected
+/// channel which is shared by every clone, so commands that make several
+/// calls reuse the same connection.
+#[derive(Clone)]
+pub struct DemeterClient {
+    rpc_url: String,
+    channel: Channel,
+    credential: Option<auth::Credential>,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl DemeterClient {
+    pub fn new(rpc_url: &str, options: RpcOptions) -> miette::Result<Self> {
+        let endpoint = rpc_url
+            .parse()
+            .into_diagnostic()
+            .context(format!("invalid rpc url {rpc_url}"))?;
+
+        let channel = Channel::builder(endpoint)
+            .connect_timeout(options.timeout)
+            .timeout(options.timeout)
+            .connect_lazy();
+
+        Ok(Self {
+            rpc_url: rpc_url.to_owned(),
+            channel,
+            credential: None,
+            timeout: options.timeout,
+            retries: options.retries,
+        })
+    }
+
+    /// Returns a client that shares the same connection but authenticates
+    /// each request with the given credential.
+    pub fn with_credential(&self, credential: auth::Credential) -> Self {
+        Self {
+            rpc_url: self.rpc_url.clone(),
+            channel: self.channel.clone(),
+            credential: Some(credential),
+            timeout: self.timeout,
+            retries: self.retries,
+        }
+    }
+
+    /// Returns a client with the same settings for another endpoint, reusing
+    /// this connection when it's the same one.
+    pub fn with_url(&self, rpc_url: &str) -> miette::Result<Self> {
+        if rpc_url == self.rpc_url {
+            return Ok(self.clone());
+        }
+
+        let options = RpcOptions {
+            timeout: self.timeout,
+            retries: self.retries,
+        };
+
+        Self::new(rpc_url, options)
+    }
+
+    /// The endpoint this client talks to.
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Runs an idempotent call, retrying it with backoff while it fails with
+    /// a transient error. Never use it for calls that mutate state.
+    async fn with_retries<T, F, Fut>(&self, operation: &str, mut call: F) -> Result<T, Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            match call().await {
+                Err(status) if attempt < self.retries && is_transient(&status) => {
+                    let delay = backoff(attempt);
+                    attempt += 1;
+
+                    warn!(
+                        operation,
+                        attempt,
+                        retries = self.retries,
+                        code = ?status.code(),
+                        delay_ms = delay.as_millis() as u64,
+                        "transient rpc error, retrying"
+                    );
+
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn authenticated_channel(&self) -> AuthenticatedChannel {
+        let interceptor = auth::CredentialInterceptor::new(self.credential.clone());
+        InterceptedService::new(self.channel.clone(), interceptor)
+    }
+}
+
+impl std::fmt::Debug for DemeterClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the credential is left out on purpose, it holds secrets
+        f.debug_struct("DemeterClient")
+            .field("rpc_url", &self.rpc_url)
+            .field("channel", &self.channel)
+            .field("retries", &self.retries)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_unavailable_and_timeouts() {
+        assert!(is_transient(&Status::unavailable("connection refused")));
+        assert!(is_transient(&Status::deadline_exceeded(
+            "deadline exceeded"
+        )));
+
+        // the channel timeout comes back as a cancellation
+        assert!(is_transient(&Status::cancelled("Timeout expired")));
+    }
+
+    #[test]
+    fn doesnt_retry_other_errors() {
+        assert!(!is_transient(&Status::cancelled(
+            "the request was cancelled"
+        )));
+        assert!(!is_transient(&Status::resource_exhausted("quota exceeded")));
+        assert!(!is_transient(&Status::not_found("port not found")));
+        assert!(!is_transient(&Status::invalid_argument("invalid spec")));
+        assert!(!is_transient(&Status::internal("internal error")));
+    }
+
+    fn full_page(first: u32) -> miette::Result<Vec<u32>> {
+        Ok((first..first + DEFAULT_PAGE_SIZE).collect())
+    }
+
+    #[tokio::test]
+    async fn fetches_pages_until_a_short_one() {
+        let records = fetch_all_pages(|page| async move {
+            match page {
+                1 | 2 => full_page(page * 1000),
+                _ => Ok(vec![1, 2, 3]),
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(records.len(), 2 * DEFAULT_PAGE_SIZE as usize + 3);
+    }
+
+    #[tokio::test]
+    async fn stops_when_the_server_ignores_paging() {
+        let records = fetch_all_pages(|_| async { full_page(0) }).await.unwrap();
+
+        assert_eq!(records.len(), DEFAULT_PAGE_SIZE as usize);
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_page_limit() {
+        let records = fetch_all_pages(|page| async move { full_page(page * 1000) })
+            .await
+            .unwrap();
+
+        assert_eq!(records.len(), (MAX_PAGES * DEFAULT_PAGE_SIZE) as usize);
+    }
+
+    #[test]
+    fn backoff_stays_under_the_ceiling() {
+        for attempt in 0..10 {
+            let ceiling = BACKOFF_BASE
+                .saturating_mul(2u32.saturating_pow(attempt))
+                .min(BACKOFF_MAX);
+
+            assert!(backoff(attempt) <= ceiling);
+        }
+    }
 }
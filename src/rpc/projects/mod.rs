@@ -1,124 +1,184 @@
 use dmtri::demeter::ops::v1alpha as proto;
-use miette::IntoDiagnostic;
-use tonic::transport::Channel;
 
-use crate::{
-    init::project::{parse_project_ref, ProjectRef},
-    rpc::auth,
-};
-
-use super::get_base_url;
-
-pub async fn find(access_token: &str) -> miette::Result<Vec<proto::Project>> {
-    let credential = auth::Credential::Auth0(access_token.to_owned());
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client =
-        proto::project_service_client::ProjectServiceClient::with_interceptor(channel, interceptor);
-
-    let request = tonic::Request::new(proto::FetchProjectsRequest {
-        page: Some(1),
-        page_size: Some(100),
-    });
-
-    let response = client.fetch_projects(request).await.into_diagnostic()?;
-    let records = response.into_inner().records;
-
-    Ok(records)
-}
-
-pub async fn find_by_namespace(
-    credential: auth::Credential,
-    namespace: &str,
-) -> miette::Result<proto::Project> {
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client =
-        proto::project_service_client::ProjectServiceClient::with_interceptor(channel, interceptor);
-
-    let request = tonic::Request::new(proto::FetchProjectByNamespaceRequest {
-        namespace: namespace.into(),
-    });
-
-    let response = client
-        .fetch_project_by_namespace(request)
-        .await
-        .into_diagnostic()?;
-
-    let record = &response.into_inner().records[0];
-
-    Ok(record.clone())
-}
-
-pub async fn create_project(access_token: &str, name: &str) -> miette::Result<ProjectRef> {
-    let credential = auth::Credential::Auth0(access_token.to_owned());
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client =
-        proto::project_service_client::ProjectServiceClient::with_interceptor(channel, interceptor);
-
-    let request = tonic::Request::new(proto::CreateProjectRequest {
-        name: name.to_owned(),
-    });
-
-    let response = client.create_project(request).await.into_diagnostic()?;
-    let projec_inner = response.into_inner();
-    let id = projec_inner.id;
-    let name = projec_inner.name;
-    let namespace = projec_inner.namespace;
-
-    let project = parse_project_ref(id, namespace, name);
-
-    Ok(project)
-}
-
-pub async fn create_secret(
-    access_token: &str,
-    project_id: &str,
-    name: &str,
-) -> miette::Result<String> {
-    let credential = auth::Credential::Auth0(access_token.to_owned());
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client =
-        proto::project_service_client::ProjectServiceClient::with_interceptor(channel, interceptor);
-
-    let request = tonic::Request::new(proto::CreateProjectSecretRequest {
-        project_id: project_id.to_owned(),
-        name: name.to_owned(),
-    });
-
-    let response = client
-        .create_project_secret(request)
-        .await
-        .into_diagnostic()?;
-
-    let api_key = response.into_inner().key;
-    println!("API key: {:?}", api_key);
-
-    Ok(api_key)
+use crate::errors::{Error, RpcError};
+use crate::init::project::{parse_project_ref, ProjectRef};
+
+use super::{fetch_all_pages, AuthenticatedChannel, DemeterClient, DEFAULT_PAGE_SIZE};
+
+type ProjectServiceClient =
+    proto::project_service_client::ProjectServiceClient<AuthenticatedChannel>;
+
+impl DemeterClient {
+    fn project_service(&self) -> ProjectServiceClient {
+        ProjectServiceClient::new(self.authenticated_channel())
+    }
+
+    /// Fetches every project the credential has access to, page by page.
+    pub async fn find_projects(&self) -> miette::Result<Vec<proto::Project>> {
+        fetch_all_pages(|page| self.find_projects_page(page, DEFAULT_PAGE_SIZE)).await
+    }
+
+    /// Fetches a single page of projects, pages start at 1.
+    pub async fn find_projects_page(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> miette::Result<Vec<proto::Project>> {
+        let response = self
+            .with_retries("fetch_projects", || {
+                let mut client = self.project_service();
+
+                let request = tonic::Request::new(proto::FetchProjectsRequest {
+                    page: Some(page),
+                    page_size: Some(page_size),
+                });
+
+                async move { client.fetch_projects(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        let records = response.into_inner().records;
+
+        Ok(records)
+    }
+
+    pub async fn find_project_by_namespace(
+        &self,
+        namespace: &str,
+    ) -> miette::Result<proto::Project> {
+        let response = self
+            .with_retries("fetch_project_by_namespace", || {
+                let mut client = self.project_service();
+
+                let request = tonic::Request::new(proto::FetchProjectByNamespaceRequest {
+                    namespace: namespace.into(),
+                });
+
+                async move { client.fetch_project_by_namespace(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        let record = response
+            .into_inner()
+            .records
+            .into_iter()
+            .next()
+            .ok_or(Error::NotFound(format!("project {namespace} not found")))?;
+
+        Ok(record)
+    }
+
+    pub async fn create_project(&self, name: &str) -> miette::Result<ProjectRef> {
+        let mut client = self.project_service();
+
+        let request = tonic::Request::new(proto::CreateProjectRequest {
+            name: name.to_owned(),
+        });
+
+        let response = client
+            .create_project(request)
+            .await
+            .map_err(RpcError::from)?;
+        let projec_inner = response.into_inner();
+        let id = projec_inner.id;
+        let name = projec_inner.name;
+        let namespace = projec_inner.namespace;
+
+        let project = parse_project_ref(id, namespace, name);
+
+        Ok(project)
+    }
+
+    pub async fn update_project(&self, id: &str, name: &str) -> miette::Result<proto::Project> {
+        let mut client = self.project_service();
+
+        let request = tonic::Request::new(proto::UpdateProjectRequest {
+            id: id.to_owned(),
+            name: name.to_owned(),
+        });
+
+        let response = client
+            .update_project(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        let updated = response
+            .into_inner()
+            .updated
+            .ok_or(Error::NotFound(format!("project {id} not found")))?;
+
+        Ok(updated)
+    }
+
+    pub async fn delete_project(&self, id: &str) -> miette::Result<()> {
+        let mut client = self.project_service();
+
+        let request = tonic::Request::new(proto::DeleteProjectRequest { id: id.to_owned() });
+
+        client
+            .delete_project(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(())
+    }
+
+    /// Creates a new api key for the project, the response is the only time
+    /// the key itself is returned.
+    pub async fn create_project_secret(
+        &self,
+        project_id: &str,
+        name: &str,
+    ) -> miette::Result<proto::CreateProjectSecretResponse> {
+        let mut client = self.project_service();
+
+        let request = tonic::Request::new(proto::CreateProjectSecretRequest {
+            project_id: project_id.to_owned(),
+            name: name.to_owned(),
+        });
+
+        let response = client
+            .create_project_secret(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Lists the api keys of the project, without the keys themselves.
+    pub async fn find_project_secrets(
+        &self,
+        project_id: &str,
+    ) -> miette::Result<Vec<proto::ProjectSecret>> {
+        let response = self
+            .with_retries("fetch_project_secrets", || {
+                let mut client = self.project_service();
+
+                let request = tonic::Request::new(proto::FetchProjectSecretsRequest {
+                    project_id: project_id.to_owned(),
+                });
+
+                async move { client.fetch_project_secrets(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(response.into_inner().records)
+    }
+
+    /// Revokes an api key, requests using it fail from then on.
+    pub async fn delete_project_secret(&self, id: &str) -> miette::Result<()> {
+        let mut client = self.project_service();
+
+        let request = tonic::Request::new(proto::DeleteProjectSecretRequest { id: id.to_owned() });
+
+        client
+            .delete_project_secret(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(())
+    }
 }
@@ -1,126 +1,137 @@
 use dmtri::demeter::ops::v1alpha as proto;
-use miette::IntoDiagnostic;
-use tonic::transport::Channel;
 
-use crate::rpc::{auth, get_base_url};
-
-pub async fn find(api_key: &str, project_id: &str) -> miette::Result<Vec<proto::Resource>> {
-    let credential = auth::Credential::Secret((project_id.to_owned(), api_key.to_owned()));
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client = proto::resource_service_client::ResourceServiceClient::with_interceptor(
-        channel,
-        interceptor,
-    );
-
-    let request = tonic::Request::new(proto::FetchResourcesRequest {
-        project_id: project_id.to_owned(),
-        page: Some(1),
-        page_size: Some(100),
-    });
-
-    let response = client.fetch_resources(request).await.into_diagnostic()?;
-    let records = response.into_inner().records;
-
-    Ok(records)
-}
-
-pub async fn find_by_id(
-    access_token: &str,
-    project_id: &str,
-    resource_id: &str,
-) -> miette::Result<Vec<proto::Resource>> {
-    let credential = auth::Credential::Secret((project_id.to_owned(), access_token.to_owned()));
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client = proto::resource_service_client::ResourceServiceClient::with_interceptor(
-        channel,
-        interceptor,
-    );
-
-    let request = tonic::Request::new(proto::FetchResourcesByIdRequest {
-        id: resource_id.into(),
-    });
-
-    let response = client
-        .fetch_resources_by_id(request)
-        .await
-        .into_diagnostic()?;
-
-    let resource = response.into_inner().records;
-
-    Ok(resource)
-}
-
-pub async fn create(
-    access_token: &str,
-    project_id: &str,
-    kind: &str,
-    spec: &str,
-) -> miette::Result<proto::Resource> {
-    let credential = auth::Credential::Secret((project_id.to_owned(), access_token.to_owned()));
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client = proto::resource_service_client::ResourceServiceClient::with_interceptor(
-        channel,
-        interceptor,
-    );
-
-    let request = tonic::Request::new(proto::CreateResourceRequest {
-        project_id: project_id.to_owned(),
-        kind: kind.to_owned(),
-        spec: spec.to_owned(),
-    });
-
-    let response = client.create_resource(request).await.into_diagnostic()?;
-
-    let resource = response.into_inner();
-    let id = resource.id;
-    let kind = resource.kind;
-
-    Ok(proto::Resource {
-        id,
-        kind,
-        ..Default::default()
-    })
-}
-
-pub async fn delete(access_token: &str, project_id: &str, id: &str) -> miette::Result<()> {
-    let credential = auth::Credential::Secret((project_id.to_owned(), access_token.to_owned()));
-    let interceptor = auth::interceptor(credential).await;
-
-    let rpc_url = get_base_url();
-    let channel = Channel::builder(rpc_url.parse().into_diagnostic()?)
-        .connect()
-        .await
-        .into_diagnostic()?;
-
-    let mut client = proto::resource_service_client::ResourceServiceClient::with_interceptor(
-        channel,
-        interceptor,
-    );
-
-    let request = tonic::Request::new(proto::DeleteResourceRequest { id: id.into() });
-
-    client.delete_resource(request).await.into_diagnostic()?;
-
-    Ok(())
+use crate::errors::RpcError;
+
+use super::{fetch_all_pages, AuthenticatedChannel, DemeterClient, DEFAULT_PAGE_SIZE};
+
+type ResourceServiceClient =
+    proto::resource_service_client::ResourceServiceClient<AuthenticatedChannel>;
+
+impl DemeterClient {
+    fn resource_service(&self) -> ResourceServiceClient {
+        ResourceServiceClient::new(self.authenticated_channel())
+    }
+
+    /// Fetches every port of the project, page by page.
+    pub async fn find_resources(&self, project_id: &str) -> miette::Result<Vec<proto::Resource>> {
+        fetch_all_pages(|page| self.find_resources_page(project_id, page, DEFAULT_PAGE_SIZE)).await
+    }
+
+    /// Fetches a single page of ports, pages start at 1.
+    pub async fn find_resources_page(
+        &self,
+        project_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> miette::Result<Vec<proto::Resource>> {
+        let response = self
+            .with_retries("fetch_resources", || {
+                let mut client = self.resource_service();
+
+                let request = tonic::Request::new(proto::FetchResourcesRequest {
+                    project_id: project_id.to_owned(),
+                    page: Some(page),
+                    page_size: Some(page_size),
+                });
+
+                async move { client.fetch_resources(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        let records = response.into_inner().records;
+
+        Ok(records)
+    }
+
+    pub async fn find_resources_by_id(
+        &self,
+        resource_id: &str,
+    ) -> miette::Result<Vec<proto::Resource>> {
+        let response = self
+            .with_retries("fetch_resources_by_id", || {
+                let mut client = self.resource_service();
+
+                let request = tonic::Request::new(proto::FetchResourcesByIdRequest {
+                    id: resource_id.into(),
+                });
+
+                async move { client.fetch_resources_by_id(request).await }
+            })
+            .await
+            .map_err(RpcError::from)?;
+
+        let resource = response.into_inner().records;
+
+        Ok(resource)
+    }
+
+    pub async fn create_resource(
+        &self,
+        project_id: &str,
+        kind: &str,
+        spec: &str,
+    ) -> miette::Result<proto::Resource> {
+        let mut client = self.resource_service();
+
+        let request = tonic::Request::new(proto::CreateResourceRequest {
+            project_id: project_id.to_owned(),
+            kind: kind.to_owned(),
+            spec: spec.to_owned(),
+        });
+
+        let response = client
+            .create_resource(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        let resource = response.into_inner();
+        let id = resource.id;
+        let kind = resource.kind;
+
+        Ok(proto::Resource {
+            id,
+            kind,
+            spec: spec.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    pub async fn update_resource(
+        &self,
+        id: &str,
+        spec_patch: &str,
+    ) -> miette::Result<proto::Resource> {
+        let mut client = self.resource_service();
+
+        let request = tonic::Request::new(proto::UpdateResourceRequest {
+            id: id.into(),
+            spec_patch: spec_patch.into(),
+        });
+
+        let response = client
+            .update_resource(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        let resource = response
+            .into_inner()
+            .updated
+            .ok_or(miette::miette!("missing updated resource in response"))?;
+
+        Ok(resource)
+    }
+
+    pub async fn delete_resource(&self, id: &str) -> miette::Result<()> {
+        let mut client = self.resource_service();
+
+        let request = tonic::Request::new(proto::DeleteResourceRequest { id: id.into() });
+
+        client
+            .delete_resource(request)
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,240 @@
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use miette::{Context as _, IntoDiagnostic};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf, sync::Mutex};
+
+use crate::{errors::Error, files};
+
+use super::SecretStore;
+
+const FILE_NAME: &str = "secrets.enc";
+
+/// Lets scripts and CI unlock the file without a prompt
+const PASSPHRASE_ENV: &str = "DMTR_SECRETS_PASSPHRASE";
+
+/// On-disk layout of the secrets file. The secrets map is serialized as json
+/// and encrypted with XChaCha20-Poly1305, using a key derived from the
+/// passphrase with Argon2id.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u8,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+struct Unlocked {
+    path: PathBuf,
+    passphrase: String,
+    secrets: BTreeMap<String, String>,
+}
+
+/// Secrets decrypted during this run, so the passphrase is asked only once.
+static UNLOCKED: Mutex<Option<Unlocked>> = Mutex::new(None);
+
+pub struct FileStore {
+    path: PathBuf,
+    lock: PathBuf,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Tests run in parallel, so they can't share the passphrase through the
+    /// env var
+    static TEST_PASSPHRASE: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Runs `f` with the passphrase set for the current thread.
+#[cfg(test)]
+pub fn with_passphrase<T>(passphrase: &str, f: impl FnOnce() -> T) -> T {
+    TEST_PASSPHRASE.with(|x| x.replace(Some(passphrase.to_owned())));
+    let out = f();
+    TEST_PASSPHRASE.with(|x| x.take());
+
+    out
+}
+
+fn ask_passphrase(creating: bool) -> miette::Result<String> {
+    #[cfg(test)]
+    if let Some(x) = TEST_PASSPHRASE.with(|x| x.borrow().clone()) {
+        return Ok(x);
+    }
+
+    if let Ok(x) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(x);
+    }
+
+    let prompt = match creating {
+        true => inquire::Password::new("Choose a passphrase to encrypt your api keys:"),
+        false => inquire::Password::new("Passphrase for your api keys:").without_confirmation(),
+    };
+
+    let passphrase = prompt.prompt().map_err(Error::from)?;
+
+    Ok(passphrase)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> miette::Result<XChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| miette::miette!("deriving secrets key: {err}"))?;
+
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn decode(field: &str, value: &str) -> miette::Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .into_diagnostic()
+        .context(format!("decoding {field} of secrets file"))
+}
+
+impl FileStore {
+    pub fn new(dirs: &crate::dirs::Dirs) -> Self {
+        Self {
+            path: dirs.root_dir().join(FILE_NAME),
+            // the config lock, since secrets are written along with the config
+            lock: dirs.root_dir().join("config.lock"),
+        }
+    }
+
+    /// Reads and decrypts the file, asking for the passphrase unless it's
+    /// already known.
+    fn unlock(&self, passphrase: Option<String>) -> miette::Result<Unlocked> {
+        if !self.path.exists() {
+            let passphrase = match passphrase {
+                Some(x) => x,
+                None => ask_passphrase(true)?,
+            };
+
+            return Ok(Unlocked {
+                path: self.path.clone(),
+                passphrase,
+                secrets: BTreeMap::new(),
+            });
+        }
+
+        let raw = std::fs::read_to_string(&self.path)
+            .into_diagnostic()
+            .context("reading secrets file")?;
+
+        let envelope: Envelope = serde_json::from_str(&raw)
+            .into_diagnostic()
+            .context("parsing secrets file")?;
+
+        let passphrase = match passphrase {
+            Some(x) => x,
+            None => ask_passphrase(false)?,
+        };
+
+        let cipher = derive_key(&passphrase, &decode("salt", &envelope.salt)?)?;
+        let nonce = decode("nonce", &envelope.nonce)?;
+
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                decode("ciphertext", &envelope.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| {
+                Error::Unauthenticated("can't decrypt the secrets file, wrong passphrase?".into())
+            })?;
+
+        let secrets = serde_json::from_slice(&plaintext)
+            .into_diagnostic()
+            .context("parsing decrypted secrets")?;
+
+        Ok(Unlocked {
+            path: self.path.clone(),
+            passphrase,
+            secrets,
+        })
+    }
+
+    fn save(&self, unlocked: &Unlocked) -> miette::Result<()> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let mut nonce = [0u8; 24];
+        OsRng.fill_bytes(&mut nonce);
+
+        let plaintext = serde_json::to_vec(&unlocked.secrets).into_diagnostic()?;
+
+        let ciphertext = derive_key(&unlocked.passphrase, &salt)?
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| miette::miette!("encrypting secrets file"))?;
+
+        let envelope = Envelope {
+            version: 1,
+            salt: STANDARD.encode(salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+
+        let contents = serde_json::to_vec(&envelope).into_diagnostic()?;
+
+        files::write_private(&self.path, &contents).context("writing secrets file")
+    }
+
+    /// Runs `f` over the decrypted secrets. With `fresh` the file is read
+    /// again even if it was already decrypted, so that changes made by other
+    /// processes aren't lost when writing.
+    fn with_unlocked<T>(
+        &self,
+        fresh: bool,
+        f: impl FnOnce(&mut Unlocked) -> miette::Result<T>,
+    ) -> miette::Result<T> {
+        let mut guard = UNLOCKED.lock().unwrap_or_else(|x| x.into_inner());
+
+        let cached = guard.take().filter(|x| x.path == self.path);
+
+        let mut unlocked = match cached {
+            Some(x) if !fresh => x,
+            Some(x) => self.unlock(Some(x.passphrase))?,
+            None => self.unlock(None)?,
+        };
+
+        let out = f(&mut unlocked);
+        *guard = Some(unlocked);
+
+        out
+    }
+}
+
+impl SecretStore for FileStore {
+    fn get(&self, id: &str) -> miette::Result<Option<String>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        self.with_unlocked(false, |x| Ok(x.secrets.get(id).cloned()))
+    }
+
+    fn set(&self, id: &str, secret: &str) -> miette::Result<()> {
+        let _lock = files::lock_exclusive(&self.lock)?;
+
+        self.with_unlocked(true, |x| {
+            x.secrets.insert(id.to_owned(), secret.to_owned());
+            self.save(x)
+        })
+    }
+
+    fn delete(&self, id: &str) -> miette::Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+
+        let _lock = files::lock_exclusive(&self.lock)?;
+
+        self.with_unlocked(true, |x| match x.secrets.remove(id) {
+            Some(_) => self.save(x),
+            None => Ok(()),
+        })
+    }
+}
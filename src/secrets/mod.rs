@@ -0,0 +1,134 @@
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::errors::Error;
+
+mod file;
+mod os_keyring;
+
+#[cfg(test)]
+pub use file::with_passphrase;
+
+/// Where secrets such as api keys are kept, so that the config file only
+/// holds a reference to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The OS keyring (Secret Service, macOS Keychain or Windows Credential
+    /// Manager)
+    Keyring,
+
+    /// A local file encrypted with a passphrase, for headless machines
+    File,
+}
+
+impl Backend {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Keyring => "keyring",
+            Backend::File => "file",
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Points to a secret in one of the backends, written to the config as
+/// `<backend>:<id>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SecretRef {
+    pub backend: Backend,
+    pub id: String,
+}
+
+impl TryFrom<String> for SecretRef {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (backend, id) = value
+            .split_once(':')
+            .ok_or(format!("invalid secret reference {value}"))?;
+
+        let backend = match backend {
+            "keyring" => Backend::Keyring,
+            "file" => Backend::File,
+            x => return Err(format!("unknown secret store {x}")),
+        };
+
+        Ok(Self {
+            backend,
+            id: id.to_owned(),
+        })
+    }
+}
+
+impl From<SecretRef> for String {
+    fn from(value: SecretRef) -> Self {
+        format!("{}:{}", value.backend, value.id)
+    }
+}
+
+trait SecretStore {
+    fn get(&self, id: &str) -> miette::Result<Option<String>>;
+    fn set(&self, id: &str, secret: &str) -> miette::Result<()>;
+    fn delete(&self, id: &str) -> miette::Result<()>;
+}
+
+fn open(backend: Backend, dirs: &crate::dirs::Dirs) -> Box<dyn SecretStore> {
+    match backend {
+        Backend::Keyring => Box::new(os_keyring::KeyringStore),
+        Backend::File => Box::new(file::FileStore::new(dirs)),
+    }
+}
+
+fn generate_id() -> String {
+    let mut bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Reads the secret a reference points to.
+pub fn load(secret: &SecretRef, dirs: &crate::dirs::Dirs) -> miette::Result<String> {
+    open(secret.backend, dirs).get(&secret.id)?.ok_or(
+        Error::Unauthenticated(format!(
+            "api key not found in the {} secret store",
+            secret.backend
+        ))
+        .into(),
+    )
+}
+
+/// Saves a new secret and returns the reference to it. Without an explicit
+/// backend the OS keyring is preferred, falling back to the encrypted file
+/// when there's no keyring available (eg: headless Linux).
+pub fn store(
+    secret: &str,
+    backend: Option<Backend>,
+    dirs: &crate::dirs::Dirs,
+) -> miette::Result<SecretRef> {
+    let id = generate_id();
+
+    let backend = match backend {
+        Some(x) => x,
+        None if os_keyring::is_available() => Backend::Keyring,
+        None => {
+            debug!("no OS keyring available, using the encrypted file store");
+            Backend::File
+        }
+    };
+
+    open(backend, dirs).set(&id, secret)?;
+
+    Ok(SecretRef { backend, id })
+}
+
+pub fn delete(secret: &SecretRef, dirs: &crate::dirs::Dirs) -> miette::Result<()> {
+    open(secret.backend, dirs).delete(&secret.id)
+}
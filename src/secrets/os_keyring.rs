@@ -0,0 +1,54 @@
+use keyring::Entry;
+use miette::{Context as _, IntoDiagnostic};
+
+use super::SecretStore;
+
+const SERVICE: &str = "dmtrctl";
+
+pub struct KeyringStore;
+
+/// Probes the platform keyring with a lookup that's expected to miss, any
+/// answer other than a platform or access failure means it's usable.
+pub fn is_available() -> bool {
+    let entry = match Entry::new(SERVICE, "probe") {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+
+    !matches!(
+        entry.get_password(),
+        Err(keyring::Error::PlatformFailure(_)) | Err(keyring::Error::NoStorageAccess(_))
+    )
+}
+
+impl SecretStore for KeyringStore {
+    fn get(&self, id: &str) -> miette::Result<Option<String>> {
+        let entry = Entry::new(SERVICE, id).into_diagnostic()?;
+
+        match entry.get_password() {
+            Ok(x) => Ok(Some(x)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err)
+                .into_diagnostic()
+                .context("reading from OS keyring"),
+        }
+    }
+
+    fn set(&self, id: &str, secret: &str) -> miette::Result<()> {
+        Entry::new(SERVICE, id)
+            .and_then(|x| x.set_password(secret))
+            .into_diagnostic()
+            .context("writing to OS keyring")
+    }
+
+    fn delete(&self, id: &str) -> miette::Result<()> {
+        let entry = Entry::new(SERVICE, id).into_diagnostic()?;
+
+        match entry.delete_credential() {
+            Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err)
+                .into_diagnostic()
+                .context("deleting from OS keyring"),
+        }
+    }
+}
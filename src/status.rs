@@ -0,0 +1,140 @@
+use clap::{parser::ValueSource, Parser};
+use std::time::Instant;
+
+use crate::{
+    context::{mask_secret, ContextOrigin, ContextSource},
+    errors::Error,
+    rpc,
+};
+
+#[derive(Parser)]
+pub struct Args {
+    /// skip the live check against the API
+    #[arg(long, action)]
+    offline: bool,
+}
+
+fn flag_source(source: Option<ValueSource>, flag: &str, env: &str) -> String {
+    match source {
+        Some(ValueSource::EnvVariable) => env.to_owned(),
+        _ => flag.to_owned(),
+    }
+}
+
+fn context_source(origin: &ContextOrigin) -> String {
+    match &origin.source {
+        ContextSource::Default => "default context in the config".to_owned(),
+        x => x.to_string(),
+    }
+}
+
+pub async fn run(args: Args, cli: &crate::Cli) -> miette::Result<()> {
+    let (ctx, origin) = match (&cli.context, &cli.context_origin) {
+        (Some(ctx), Some(origin)) => (ctx, origin),
+        _ => {
+            println!("Context:    <none>");
+            println!("RPC URL:    {}", cli.client.rpc_url());
+
+            return Err(Error::Unauthenticated("no context in use".into()).into());
+        }
+    };
+
+    let explicit = matches!(origin.source, ContextSource::Credentials);
+
+    let (namespace_source, key_source) = match explicit {
+        true => (
+            flag_source(cli.sources.namespace, "--namespace", "DMTR_NAMESPACE"),
+            flag_source(cli.sources.api_key, "--api-key", "DMTR_API_KEY"),
+        ),
+        false => {
+            let key_source = match &ctx.auth.secret {
+                Some(x) => format!("{} secret store", x.backend),
+                None => "config file".to_owned(),
+            };
+
+            (format!("context {}", origin.name), key_source)
+        }
+    };
+
+    let url_source = match (&ctx.rpc_url, std::env::var("RPC_BASE_URL")) {
+        (Some(_), _) => format!("context {}", origin.name),
+        (_, Ok(_)) => "RPC_BASE_URL".to_owned(),
+        _ => "default".to_owned(),
+    };
+
+    match explicit {
+        true => println!("Context:    <none>, using explicit credentials"),
+        false => println!(
+            "Context:    {} (from {})",
+            origin.name,
+            context_source(origin)
+        ),
+    }
+
+    println!(
+        "Namespace:  {} (from {namespace_source})",
+        ctx.namespace.name
+    );
+
+    if !ctx.auth.has_credentials() {
+        println!("API Key:    <logged out>");
+        println!("RPC URL:    {} (from {url_source})", cli.client.rpc_url());
+
+        return Err(Error::Unauthenticated("the context was logged out".into()).into());
+    }
+
+    let api_key = ctx.auth.resolve_token(&cli.dirs)?;
+
+    println!("API Key:    {} (from {key_source})", mask_secret(&api_key));
+    println!("RPC URL:    {} (from {url_source})", cli.client.rpc_url());
+
+    // a named context wins over the credential flags, which are dropped
+    if !explicit {
+        let ignored = [
+            cli.sources
+                .namespace
+                .map(|x| flag_source(Some(x), "--namespace", "DMTR_NAMESPACE")),
+            cli.sources
+                .api_key
+                .map(|x| flag_source(Some(x), "--api-key", "DMTR_API_KEY")),
+        ];
+
+        let ignored: Vec<_> = ignored.into_iter().flatten().collect();
+
+        if !ignored.is_empty() {
+            println!(
+                "Ignored:    {}, a context was selected",
+                ignored.join(" and ")
+            );
+        }
+    }
+
+    if args.offline {
+        return Ok(());
+    }
+
+    let started = Instant::now();
+
+    let project = cli
+        .client
+        .with_credential(rpc::auth::Credential::Secret((
+            ctx.namespace.name.clone(),
+            api_key,
+        )))
+        .find_project_by_namespace(&ctx.namespace.name)
+        .await;
+
+    let elapsed = started.elapsed().as_millis();
+
+    match project {
+        Ok(project) => {
+            println!("Project:    {} ({})", project.name, project.id);
+            println!("Check:      ok ({elapsed} ms)");
+            Ok(())
+        }
+        Err(err) => {
+            println!("Check:      failed ({elapsed} ms)");
+            Err(err)
+        }
+    }
+}